@@ -1,10 +1,21 @@
 use clap::{Parser, Subcommand};
+use schemaforge::backend::sqlite::compile_create_table_sql;
 use schemaforge::build;
+use schemaforge::compat;
+use schemaforge::diff;
+use schemaforge::explain;
+use schemaforge::format;
+use schemaforge::ir::ast::{parse_kdl, AstSchema};
+use schemaforge::ir::schema::ResolvedSchema;
+use schemaforge::load;
+use schemaforge::lower::lower_queries;
+use schemaforge::passes;
 use schemaforge::registry;
 use schemaforge::Error;
+use std::collections::HashMap;
 use std::fs;
 use std::io::{self, Read, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Parser)]
 #[command(name = "schemaforge")]
@@ -26,6 +37,48 @@ enum Commands {
     },
     Build {
         input: PathBuf,
+        #[arg(long)]
+        out_dir: Option<PathBuf>,
+        #[arg(long)]
+        crate_name: Option<String>,
+        #[arg(long)]
+        module_name: Option<String>,
+        #[arg(long)]
+        demo: bool,
+        #[arg(long, conflicts_with = "rusqlite_workspace")]
+        rusqlite_version: Option<String>,
+        #[arg(long, value_delimiter = ',')]
+        rusqlite_features: Vec<String>,
+        #[arg(long)]
+        rusqlite_workspace: bool,
+        #[arg(long)]
+        module_only: bool,
+        #[arg(long = "async")]
+        async_api: bool,
+    },
+    Explain {
+        input: PathBuf,
+        #[arg(long)]
+        query: Option<String>,
+    },
+    Diff {
+        old: PathBuf,
+        new: PathBuf,
+        #[arg(long)]
+        json: bool,
+    },
+    CheckCompat {
+        old: PathBuf,
+        new: PathBuf,
+    },
+    Lint {
+        input: PathBuf,
+    },
+    Fmt {
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+        #[arg(long)]
+        check: bool,
     },
 }
 
@@ -57,15 +110,186 @@ fn run() -> Result<(), Error> {
             let result = (spec.run)(&input_text)?;
             write_output(&output, &result)?;
         }
-        Commands::Build { input } => {
-            let output_dir = build::build(&input)?;
+        Commands::Build {
+            input,
+            out_dir,
+            crate_name,
+            module_name,
+            demo,
+            rusqlite_version,
+            rusqlite_features,
+            rusqlite_workspace,
+            module_only,
+            async_api,
+        } => {
+            let rusqlite = if rusqlite_workspace {
+                build::RusqliteDependency::Workspace {
+                    features: rusqlite_features,
+                }
+            } else {
+                build::RusqliteDependency::Version {
+                    version: rusqlite_version.unwrap_or_else(|| {
+                        build::DEFAULT_RUSQLITE_VERSION.to_string()
+                    }),
+                    features: rusqlite_features,
+                }
+            };
+            let options = build::BuildOptions {
+                output_dir: out_dir,
+                crate_name,
+                module_name,
+                demo,
+                rusqlite,
+                module_only,
+                async_api,
+            };
+            let output_dir = build::build_with(&input, &options)?;
             println!("{}", output_dir.display());
         }
+        Commands::Explain { input, query } => {
+            let schema = passes::run_frontend(&load_input(&input)?)?;
+            let lowered = lower_queries(&schema)?;
+            let explanations =
+                explain::explain_queries(&schema, &lowered, query.as_deref())?;
+
+            for (index, explanation) in explanations.iter().enumerate() {
+                if index > 0 {
+                    println!();
+                }
+                println!("query {}", explanation.name);
+                println!("plan:");
+                print_indented(&explain::render_plan_tree(
+                    &explanation.plan,
+                    &schema,
+                ));
+                println!("sql:");
+                println!("  {}", explanation.sql.sql);
+                println!("sqlite:");
+                for line in sqlite_query_plan(&schema, &explanation.sql.sql)? {
+                    println!("  {}", line);
+                }
+            }
+        }
+        Commands::Diff { old, new, json } => {
+            let old_schema = resolve_file(&old)?;
+            let new_schema = resolve_file(&new)?;
+            let changes = diff::diff_schemas(&old_schema, &new_schema);
+            if json {
+                print!("{}", diff::render_json(&changes));
+            } else {
+                print!("{}", diff::render_text(&changes));
+            }
+        }
+        Commands::Lint { input } => {
+            let loaded = load::load_schema(&input)?;
+            let schema = passes::run_frontend(&loaded.ast)?;
+            for warning in passes::lint::run(&schema, &loaded.ast.lint_allow)? {
+                println!("{}", warning);
+            }
+        }
+        Commands::Fmt { files, check } => {
+            let mut unformatted = 0;
+            for file in &files {
+                let src = read_input(file)?;
+                let formatted = format::format_schema(&src).map_err(|err| {
+                    Error::Parse(format!("{}: {}", file.display(), err))
+                })?;
+                // `-` formats stdin to stdout, like the other commands.
+                if file.as_os_str() == "-" && !check {
+                    write_output(file, &formatted)?;
+                    continue;
+                }
+                if formatted == src {
+                    continue;
+                }
+                if check {
+                    println!("would reformat {}", file.display());
+                    unformatted += 1;
+                } else {
+                    write_output(file, &formatted)?;
+                    println!("reformatted {}", file.display());
+                }
+            }
+            if unformatted > 0 {
+                return Err(Error::Pass(format!(
+                    "{} file(s) need formatting",
+                    unformatted
+                )));
+            }
+        }
+        Commands::CheckCompat { old, new } => {
+            let old_schema = resolve_file(&old)?;
+            let new_schema = resolve_file(&new)?;
+            let changes = compat::check_compat(&old_schema, &new_schema)?;
+            print!("{}", compat::render_text(&changes));
+
+            let breaking = changes
+                .iter()
+                .filter(|change| {
+                    change.compatibility == compat::Compatibility::Breaking
+                })
+                .count();
+            if breaking > 0 {
+                return Err(Error::Pass(format!(
+                    "{} breaking change(s) to the generated API",
+                    breaking
+                )));
+            }
+        }
     }
 
     Ok(())
 }
 
+fn resolve_file(path: &Path) -> Result<ResolvedSchema, Error> {
+    let loaded = load::load_schema(path)?;
+    passes::run_frontend(&loaded.ast)
+}
+
+fn print_indented(text: &str) {
+    for line in text.lines() {
+        println!("  {}", line);
+    }
+}
+
+fn sqlite_query_plan(
+    schema: &ResolvedSchema,
+    sql: &str,
+) -> Result<Vec<String>, Error> {
+    let conn = rusqlite::Connection::open_in_memory().map_err(sqlite_error)?;
+    for table in &schema.tables {
+        conn.execute_batch(&compile_create_table_sql(table, schema)?)
+            .map_err(sqlite_error)?;
+    }
+
+    let mut stmt = conn
+        .prepare(&format!("EXPLAIN QUERY PLAN {}", sql))
+        .map_err(sqlite_error)?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, String>(3)?,
+            ))
+        })
+        .map_err(sqlite_error)?;
+
+    let mut depths = HashMap::new();
+    let mut lines = Vec::new();
+    for row in rows {
+        let (id, parent, detail) = row.map_err(sqlite_error)?;
+        let depth = depths.get(&parent).map(|depth| depth + 1).unwrap_or(0);
+        depths.insert(id, depth);
+        lines.push(format!("{}{}", "  ".repeat(depth), detail));
+    }
+    Ok(lines)
+}
+
+fn sqlite_error(err: rusqlite::Error) -> Error {
+    Error::Pass(format!("sqlite error: {}", err))
+}
+
 fn read_input(path: &PathBuf) -> Result<String, Error> {
     if path.as_os_str() == "-" {
         let mut buf = String::new();
@@ -76,6 +300,16 @@ fn read_input(path: &PathBuf) -> Result<String, Error> {
     Ok(fs::read_to_string(path)?)
 }
 
+// A schema read from stdin has no directory to resolve includes against,
+// so it is parsed on its own.
+fn load_input(path: &PathBuf) -> Result<AstSchema, Error> {
+    if path.as_os_str() == "-" {
+        return parse_kdl(&read_input(path)?);
+    }
+
+    Ok(load::load_schema(path)?.ast)
+}
+
 fn write_output(path: &PathBuf, contents: &str) -> Result<(), Error> {
     if path.as_os_str() == "-" {
         let mut stdout = io::stdout();
@@ -27,5 +27,54 @@ fn build_generates_crate_files() {
 
     assert!(output_dir.join("Cargo.toml").exists());
     assert!(output_dir.join("src/lib.rs").exists());
+    assert!(!output_dir.join("src/main.rs").exists());
+
+    let status = Command::new(binary)
+        .current_dir(&workspace_root)
+        .arg("build")
+        .arg(&fixture)
+        .arg("--demo")
+        .status()
+        .expect("run schemaforge-cli build --demo");
+    assert!(status.success());
     assert!(output_dir.join("src/main.rs").exists());
 }
+
+#[test]
+fn build_module_only_writes_one_file() {
+    let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let workspace_root =
+        manifest_dir.parent().expect("workspace root").to_path_buf();
+    let fixture =
+        workspace_root.join("schemaforge/tests/fixtures/spike/spike.in.kdl");
+    let output_dir = workspace_root.join("target/build-smoke/module-only");
+    if output_dir.exists() {
+        fs::remove_dir_all(&output_dir).expect("cleanup old output dir");
+    }
+
+    let binary = env!("CARGO_BIN_EXE_schemaforge-cli");
+    let status = Command::new(binary)
+        .arg("build")
+        .arg(&fixture)
+        .arg("--out-dir")
+        .arg(&output_dir)
+        .arg("--module-only")
+        .status()
+        .expect("run schemaforge-cli build --module-only");
+    assert!(status.success());
+    assert!(output_dir.join("spike.rs").exists());
+    assert!(!output_dir.join("Cargo.toml").exists());
+
+    let output = Command::new(binary)
+        .arg("build")
+        .arg(&fixture)
+        .arg("--out-dir")
+        .arg(&output_dir)
+        .arg("--module-only")
+        .arg("--rusqlite-features")
+        .arg("bundled")
+        .output()
+        .expect("run schemaforge-cli build --module-only");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("no Cargo.toml"));
+}
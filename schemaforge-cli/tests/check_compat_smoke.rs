@@ -0,0 +1,34 @@
+use std::path::PathBuf;
+use std::process::Command;
+
+#[test]
+fn check_compat_fails_on_breaking_changes() {
+    let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let workspace_root =
+        manifest_dir.parent().expect("workspace root").to_path_buf();
+
+    let fixtures = workspace_root.join("schemaforge/tests/fixtures/diff");
+
+    let binary = env!("CARGO_BIN_EXE_schemaforge-cli");
+    let output = Command::new(binary)
+        .arg("check-compat")
+        .arg(fixtures.join("old.kdl"))
+        .arg(fixtures.join("new.kdl"))
+        .output()
+        .expect("run schemaforge-cli check-compat");
+    assert!(!output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).expect("utf-8 stdout");
+    assert!(stdout.contains(
+        "breaking: - query people_ages: people (age: i64) (removed query method)\n"
+    ));
+    assert!(stdout.ends_with("8 breaking, 3 additive\n"));
+
+    let output = Command::new(binary)
+        .arg("check-compat")
+        .arg(fixtures.join("old.kdl"))
+        .arg(fixtures.join("old.kdl"))
+        .output()
+        .expect("run schemaforge-cli check-compat");
+    assert!(output.status.success());
+}
@@ -0,0 +1,37 @@
+use std::path::PathBuf;
+use std::process::Command;
+
+#[test]
+fn diff_prints_text_and_json() {
+    let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let workspace_root =
+        manifest_dir.parent().expect("workspace root").to_path_buf();
+
+    let fixtures = workspace_root.join("schemaforge/tests/fixtures/diff");
+
+    let binary = env!("CARGO_BIN_EXE_schemaforge-cli");
+    let output = Command::new(binary)
+        .arg("diff")
+        .arg(fixtures.join("old.kdl"))
+        .arg(fixtures.join("new.kdl"))
+        .output()
+        .expect("run schemaforge-cli diff");
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).expect("utf-8 stdout");
+    assert!(stdout.contains("~ field people.age: i64 -> text\n"));
+    assert!(stdout.contains("- table notes\n"));
+
+    let output = Command::new(binary)
+        .arg("diff")
+        .arg(fixtures.join("old.kdl"))
+        .arg(fixtures.join("new.kdl"))
+        .arg("--json")
+        .output()
+        .expect("run schemaforge-cli diff --json");
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).expect("utf-8 stdout");
+    assert!(stdout.starts_with("{\n  \"changes\": ["));
+    assert!(stdout.contains("\"name\": \"people.age\""));
+}
@@ -0,0 +1,64 @@
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+#[test]
+fn explain_prints_plan_sql_and_sqlite_plan() {
+    let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let workspace_root =
+        manifest_dir.parent().expect("workspace root").to_path_buf();
+
+    let fixture =
+        workspace_root.join("schemaforge/tests/fixtures/spike/spike.in.kdl");
+
+    let binary = env!("CARGO_BIN_EXE_schemaforge-cli");
+    let output = Command::new(binary)
+        .current_dir(&workspace_root)
+        .arg("explain")
+        .arg(&fixture)
+        .arg("--query")
+        .arg("list_names_and_ids")
+        .output()
+        .expect("run schemaforge-cli explain");
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).expect("utf-8 stdout");
+    assert!(stdout.contains("query list_names_and_ids"));
+    assert!(stdout.contains("Project people.name, people.id"));
+    assert!(stdout.contains("    TableScan people"));
+    assert!(stdout.contains("SELECT \"name\", \"id\" FROM \"people\""));
+    assert!(stdout.contains("SCAN people"));
+}
+
+#[test]
+fn explain_reads_schema_from_stdin() {
+    let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let workspace_root =
+        manifest_dir.parent().expect("workspace root").to_path_buf();
+    let schema = fs::read_to_string(
+        workspace_root.join("schemaforge/tests/fixtures/spike/spike.in.kdl"),
+    )
+    .expect("read fixture");
+
+    let binary = env!("CARGO_BIN_EXE_schemaforge-cli");
+    let mut child = Command::new(binary)
+        .arg("explain")
+        .arg("-")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("run schemaforge-cli explain -");
+    child
+        .stdin
+        .take()
+        .expect("stdin")
+        .write_all(schema.as_bytes())
+        .expect("write schema");
+    let output = child.wait_with_output().expect("wait for explain");
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).expect("utf-8 stdout");
+    assert!(stdout.contains("query list_names_and_ids"));
+    assert!(stdout.contains("SELECT \"name\", \"id\" FROM \"people\""));
+}
@@ -0,0 +1,51 @@
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+#[test]
+fn fmt_rewrites_files_and_check_reports_them() {
+    let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let workspace_root =
+        manifest_dir.parent().expect("workspace root").to_path_buf();
+    let fixtures = workspace_root.join("schemaforge/tests/fixtures/format");
+
+    let target_dir = workspace_root.join("target/fmt-smoke");
+    fs::create_dir_all(&target_dir).expect("create target dir");
+    let schema = target_dir.join("schema.kdl");
+    fs::copy(fixtures.join("messy.kdl"), &schema).expect("copy fixture");
+
+    let binary = env!("CARGO_BIN_EXE_schemaforge-cli");
+    let output = Command::new(binary)
+        .arg("fmt")
+        .arg("--check")
+        .arg(&schema)
+        .output()
+        .expect("run schemaforge-cli fmt --check");
+    assert!(!output.status.success());
+    let stdout = String::from_utf8(output.stdout).expect("utf-8 stdout");
+    assert!(stdout.starts_with("would reformat "));
+    assert_eq!(
+        fs::read_to_string(&schema).expect("read schema"),
+        fs::read_to_string(fixtures.join("messy.kdl")).expect("read fixture")
+    );
+
+    let status = Command::new(binary)
+        .arg("fmt")
+        .arg(&schema)
+        .status()
+        .expect("run schemaforge-cli fmt");
+    assert!(status.success());
+    assert_eq!(
+        fs::read_to_string(&schema).expect("read schema"),
+        fs::read_to_string(fixtures.join("formatted.kdl"))
+            .expect("read fixture")
+    );
+
+    let status = Command::new(binary)
+        .arg("fmt")
+        .arg("--check")
+        .arg(&schema)
+        .status()
+        .expect("run schemaforge-cli fmt --check");
+    assert!(status.success());
+}
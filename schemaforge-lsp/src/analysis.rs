@@ -0,0 +1,556 @@
+use crate::outline::{self, OutlineNode, Spanned};
+use schemaforge::ir::ast::parse_kdl;
+use schemaforge::load::load_schema_source;
+use schemaforge::passes;
+use schemaforge::Error;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tower_lsp::lsp_types::{
+    CompletionItem, CompletionItemKind, Diagnostic, DiagnosticSeverity, Hover,
+    HoverContents, Location, MarkupContent, MarkupKind, Position, Range, Url,
+};
+
+// Parses and resolves the document the same way `schemaforge-cli build`
+// does, reporting the first error and any lint warnings. Errors carry no
+// source positions, so each is placed on the first name it quotes.
+pub fn diagnostics(uri: &Url, text: &str) -> Vec<Diagnostic> {
+    let nodes = outline::scan(text);
+    let loaded = match uri.to_file_path() {
+        Ok(path) => load_schema_source(&path, text).map(|loaded| loaded.ast),
+        // Unsaved documents have no directory to resolve includes against.
+        Err(()) => parse_kdl(text),
+    };
+    let result = loaded.and_then(|ast| {
+        let schema = passes::run_frontend(&ast)?;
+        passes::lint::run(&schema, &ast.lint_allow)
+    });
+
+    match result {
+        Ok(warnings) => warnings
+            .into_iter()
+            .map(|warning| {
+                diagnostic(
+                    &nodes,
+                    &warning.message,
+                    format!("{} [{}]", warning.message, warning.rule),
+                    DiagnosticSeverity::WARNING,
+                )
+            })
+            .collect(),
+        Err(err) => {
+            let message = match &err {
+                Error::Parse(message) | Error::Pass(message) => message.clone(),
+                Error::Io(_) => err.to_string(),
+            };
+            vec![diagnostic(
+                &nodes,
+                &message,
+                err.to_string(),
+                DiagnosticSeverity::ERROR,
+            )]
+        }
+    }
+}
+
+pub fn definition(
+    uri: &Url,
+    text: &str,
+    position: Position,
+) -> Option<Location> {
+    let (index, occurrence) = lookup(uri, text, position)?;
+    match &occurrence.target {
+        Target::Table(name) => index
+            .symbol(SymbolKind::Table, name)
+            .map(|t| t.location.clone()),
+        Target::Mixin(name) => index
+            .symbol(SymbolKind::Mixin, name)
+            .map(|m| m.location.clone()),
+        Target::Column { owner, field } => index
+            .column(owner, field)
+            .map(|field| field.location.clone()),
+    }
+}
+
+pub fn hover(uri: &Url, text: &str, position: Position) -> Option<Hover> {
+    let (index, occurrence) = lookup(uri, text, position)?;
+    let value = match &occurrence.target {
+        Target::Table(name) => {
+            describe_symbol(index.symbol(SymbolKind::Table, name)?, &index)
+        }
+        Target::Mixin(name) => {
+            describe_symbol(index.symbol(SymbolKind::Mixin, name)?, &index)
+        }
+        Target::Column { owner, field } => {
+            describe_field(&owner.1, index.column(owner, field)?)
+        }
+    };
+
+    Some(Hover {
+        contents: HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value,
+        }),
+        range: Some(occurrence.range),
+    })
+}
+
+// Completes table names in `table=` values, mixin names in `use` nodes, and
+// column names in proc `param` and query `project` nodes.
+pub fn completion(
+    uri: &Url,
+    text: &str,
+    position: Position,
+) -> Vec<CompletionItem> {
+    let Some((index, occurrence)) = lookup(uri, text, position) else {
+        return Vec::new();
+    };
+    if !occurrence.reference {
+        return Vec::new();
+    }
+
+    match &occurrence.target {
+        Target::Table(_) | Target::Mixin(_) => {
+            let kind = match occurrence.target {
+                Target::Table(_) => SymbolKind::Table,
+                _ => SymbolKind::Mixin,
+            };
+            index
+                .symbols
+                .iter()
+                .filter(|symbol| symbol.kind == kind)
+                .map(|symbol| CompletionItem {
+                    label: symbol.name.clone(),
+                    kind: Some(CompletionItemKind::STRUCT),
+                    detail: Some(format!(
+                        "{} {}",
+                        symbol.kind.as_str(),
+                        symbol.name
+                    )),
+                    ..CompletionItem::default()
+                })
+                .collect()
+        }
+        Target::Column { owner, .. } => index
+            .columns(owner)
+            .into_iter()
+            .map(|field| CompletionItem {
+                label: field.name.clone(),
+                kind: Some(CompletionItemKind::FIELD),
+                detail: Some(field.ty.clone()),
+                ..CompletionItem::default()
+            })
+            .collect(),
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SymbolKind {
+    Table,
+    Mixin,
+}
+
+impl SymbolKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            SymbolKind::Table => "table",
+            SymbolKind::Mixin => "mixin",
+        }
+    }
+}
+
+// A table or mixin declaration, from the document or a file it includes.
+struct Symbol {
+    kind: SymbolKind,
+    name: String,
+    location: Location,
+    fields: Vec<FieldSymbol>,
+    uses: Vec<String>,
+}
+
+struct FieldSymbol {
+    name: String,
+    ty: String,
+    location: Location,
+    attributes: Vec<String>,
+}
+
+#[derive(Clone, Debug)]
+enum Target {
+    Table(String),
+    Mixin(String),
+    Column {
+        owner: (SymbolKind, String),
+        field: String,
+    },
+}
+
+// A name in the current document that refers to, or declares, a symbol.
+#[derive(Clone, Debug)]
+struct Occurrence {
+    target: Target,
+    range: Range,
+    reference: bool,
+}
+
+struct Index {
+    symbols: Vec<Symbol>,
+}
+
+impl Index {
+    fn symbol(&self, kind: SymbolKind, name: &str) -> Option<&Symbol> {
+        self.symbols
+            .iter()
+            .find(|symbol| symbol.kind == kind && symbol.name == name)
+    }
+
+    // A table's own fields, followed by the fields of the mixins it uses.
+    fn columns(
+        &self,
+        (kind, name): &(SymbolKind, String),
+    ) -> Vec<&FieldSymbol> {
+        let Some(symbol) = self.symbol(*kind, name) else {
+            return Vec::new();
+        };
+        let mut fields = symbol.fields.iter().collect::<Vec<_>>();
+        for mixin in &symbol.uses {
+            if let Some(mixin) = self.symbol(SymbolKind::Mixin, mixin) {
+                fields.extend(&mixin.fields);
+            }
+        }
+        fields
+    }
+
+    fn column(
+        &self,
+        owner: &(SymbolKind, String),
+        field: &str,
+    ) -> Option<&FieldSymbol> {
+        self.columns(owner)
+            .into_iter()
+            .find(|column| column.name == field)
+    }
+}
+
+fn lookup(
+    uri: &Url,
+    text: &str,
+    position: Position,
+) -> Option<(Index, Occurrence)> {
+    let nodes = outline::scan(text);
+    let mut index = Index {
+        symbols: Vec::new(),
+    };
+    let mut occurrences = Vec::new();
+    walk(&nodes, uri, None, &mut index.symbols, &mut occurrences);
+
+    if let Ok(path) = uri.to_file_path() {
+        let mut seen = HashSet::new();
+        if let Ok(canonical) = fs::canonicalize(&path) {
+            seen.insert(canonical);
+        }
+        index_includes(&path, &nodes, &mut seen, &mut index.symbols);
+    }
+
+    let occurrence = occurrences
+        .into_iter()
+        .find(|occurrence| outline::contains(&occurrence.range, position))?;
+    Some((index, occurrence))
+}
+
+// Adds the declarations of every file `nodes` includes, transitively. Files
+// that cannot be read are skipped; `diagnostics` reports them.
+fn index_includes(
+    path: &Path,
+    nodes: &[OutlineNode],
+    seen: &mut HashSet<PathBuf>,
+    symbols: &mut Vec<Symbol>,
+) {
+    let base = path.parent().unwrap_or_else(|| Path::new(""));
+    for node in nodes.iter().filter(|node| node.name.value == "include") {
+        let Some(include) = node.first_arg() else {
+            continue;
+        };
+        let path = base.join(&include.value);
+        let Ok(canonical) = fs::canonicalize(&path) else {
+            continue;
+        };
+        if !seen.insert(canonical) {
+            continue;
+        }
+        let (Ok(text), Ok(uri)) =
+            (fs::read_to_string(&path), Url::from_file_path(&path))
+        else {
+            continue;
+        };
+
+        let nodes = outline::scan(&text);
+        walk(&nodes, &uri, None, symbols, &mut Vec::new());
+        index_includes(&path, &nodes, seen, symbols);
+    }
+}
+
+// Module members are qualified as `module.name`, and table references
+// inside a module prefer the module's own tables, as in `ir::ast`.
+struct Module {
+    name: String,
+    tables: HashSet<String>,
+}
+
+impl Module {
+    fn qualify_table_ref(&self, name: &str) -> String {
+        if self.tables.contains(name) {
+            format!("{}.{}", self.name, name)
+        } else {
+            name.to_string()
+        }
+    }
+}
+
+fn walk(
+    nodes: &[OutlineNode],
+    uri: &Url,
+    module: Option<&Module>,
+    symbols: &mut Vec<Symbol>,
+    occurrences: &mut Vec<Occurrence>,
+) {
+    for node in nodes {
+        match node.name.value.as_str() {
+            "module" => {
+                let Some(name) = node.first_arg() else {
+                    continue;
+                };
+                let module = Module {
+                    name: name.value.clone(),
+                    tables: node
+                        .children
+                        .iter()
+                        .filter(|child| child.name.value == "table")
+                        .filter_map(|child| child.first_arg())
+                        .map(|name| name.value.clone())
+                        .collect(),
+                };
+                walk(&node.children, uri, Some(&module), symbols, occurrences);
+            }
+            "table" | "mixin" => {
+                let Some(name) = node.first_arg() else {
+                    continue;
+                };
+                let kind = if node.name.value == "table" {
+                    SymbolKind::Table
+                } else {
+                    SymbolKind::Mixin
+                };
+                let qualified = match module {
+                    Some(module) => format!("{}.{}", module.name, name.value),
+                    None => name.value.clone(),
+                };
+                occurrences.push(Occurrence {
+                    target: match kind {
+                        SymbolKind::Table => Target::Table(qualified.clone()),
+                        SymbolKind::Mixin => Target::Mixin(qualified.clone()),
+                    },
+                    range: name.range,
+                    reference: false,
+                });
+                symbols.push(declare(
+                    node,
+                    kind,
+                    qualified,
+                    name,
+                    uri,
+                    occurrences,
+                ));
+            }
+            "proc" | "query" => {
+                let Some(table) = node.prop("table") else {
+                    continue;
+                };
+                let table_name = match module {
+                    Some(module) => module.qualify_table_ref(&table.value),
+                    None => table.value.clone(),
+                };
+                occurrences.push(Occurrence {
+                    target: Target::Table(table_name.clone()),
+                    range: table.range,
+                    reference: true,
+                });
+
+                let column_node = if node.name.value == "proc" {
+                    "param"
+                } else {
+                    "project"
+                };
+                for child in &node.children {
+                    if child.name.value != column_node {
+                        continue;
+                    }
+                    if let Some(column) = child.first_arg() {
+                        occurrences.push(Occurrence {
+                            target: Target::Column {
+                                owner: (SymbolKind::Table, table_name.clone()),
+                                field: column.value.clone(),
+                            },
+                            range: column.range,
+                            reference: true,
+                        });
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn declare(
+    node: &OutlineNode,
+    kind: SymbolKind,
+    name: String,
+    name_span: &Spanned,
+    uri: &Url,
+    occurrences: &mut Vec<Occurrence>,
+) -> Symbol {
+    let mut symbol = Symbol {
+        kind,
+        name,
+        location: Location::new(uri.clone(), name_span.range),
+        fields: Vec::new(),
+        uses: Vec::new(),
+    };
+
+    for child in &node.children {
+        let Some(arg) = child.first_arg() else {
+            continue;
+        };
+        match child.name.value.as_str() {
+            "field" => {
+                occurrences.push(Occurrence {
+                    target: Target::Column {
+                        owner: (kind, symbol.name.clone()),
+                        field: arg.value.clone(),
+                    },
+                    range: arg.range,
+                    reference: false,
+                });
+                symbol.fields.push(FieldSymbol {
+                    name: arg.value.clone(),
+                    ty: child
+                        .prop("type")
+                        .map(|ty| ty.value.clone())
+                        .unwrap_or_default(),
+                    location: Location::new(uri.clone(), arg.range),
+                    attributes: field_attributes(child),
+                });
+            }
+            "use" => {
+                occurrences.push(Occurrence {
+                    target: Target::Mixin(arg.value.clone()),
+                    range: arg.range,
+                    reference: true,
+                });
+                symbol.uses.push(arg.value.clone());
+            }
+            _ => {}
+        }
+    }
+
+    symbol
+}
+
+fn field_attributes(node: &OutlineNode) -> Vec<String> {
+    let mut attributes = Vec::new();
+    if node.prop("key").map(|key| key.value.as_str()) == Some("true") {
+        attributes.push("primary key".to_string());
+    }
+    if node
+        .prop("auto-increment")
+        .map(|value| value.value.as_str())
+        == Some("true")
+    {
+        attributes.push("auto-increment".to_string());
+    }
+    if let Some(default) = node.prop("default") {
+        attributes.push(format!("default: `{}`", default.value));
+    }
+    if let Some(computed) = node.prop("computed") {
+        attributes.push(format!("computed: `{}`", computed.value));
+    }
+    attributes
+}
+
+fn describe_symbol(symbol: &Symbol, index: &Index) -> String {
+    let mut out =
+        format!("```\n{} {}\n```\n", symbol.kind.as_str(), symbol.name);
+    for field in index.columns(&(symbol.kind, symbol.name.clone())) {
+        out.push_str(&format!("\n- `{}`: `{}`", field.name, field.ty));
+    }
+    out
+}
+
+fn describe_field(owner: &str, field: &FieldSymbol) -> String {
+    let mut out = format!("```\n{}.{}: {}\n```", owner, field.name, field.ty);
+    if !field.attributes.is_empty() {
+        out.push_str(&format!("\n\n{}", field.attributes.join(", ")));
+    }
+    out
+}
+
+fn diagnostic(
+    nodes: &[OutlineNode],
+    located_by: &str,
+    message: String,
+    severity: DiagnosticSeverity,
+) -> Diagnostic {
+    Diagnostic {
+        range: error_range(nodes, located_by),
+        severity: Some(severity),
+        source: Some("schemaforge".to_string()),
+        message,
+        ..Diagnostic::default()
+    }
+}
+
+// Places a message on the first name it quotes ('like this') that appears
+// in the document, preferring the name that follows "unknown". Qualified
+// names are also tried by their last segment, and file paths by the include
+// that names them.
+fn error_range(nodes: &[OutlineNode], message: &str) -> Range {
+    let mut spans = Vec::new();
+    collect_spans(nodes, &mut spans);
+
+    let unknown = message
+        .split_once("unknown ")
+        .map(|(_, rest)| rest)
+        .unwrap_or_default();
+    let names = quoted_names(unknown).chain(quoted_names(message));
+    for quoted in names {
+        let short = quoted.rsplit('.').next().unwrap_or(quoted);
+        if let Some(span) = spans
+            .iter()
+            .find(|span| span.value == quoted)
+            .or_else(|| spans.iter().find(|span| span.value == short))
+            .or_else(|| {
+                spans.iter().find(|span| {
+                    quoted.contains('/')
+                        && Path::new(&span.value).file_name()
+                            == Path::new(quoted).file_name()
+                })
+            })
+        {
+            return span.range;
+        }
+    }
+    Range::default()
+}
+
+fn quoted_names(text: &str) -> impl Iterator<Item = &str> {
+    text.split('\'').skip(1).step_by(2)
+}
+
+fn collect_spans<'a>(nodes: &'a [OutlineNode], spans: &mut Vec<&'a Spanned>) {
+    for node in nodes {
+        spans.extend(&node.args);
+        spans.extend(node.props.iter().map(|(_, value)| value));
+        collect_spans(&node.children, spans);
+    }
+}
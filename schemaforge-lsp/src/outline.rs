@@ -0,0 +1,306 @@
+use tower_lsp::lsp_types::{Position, Range};
+
+// A string or bare word from the source together with where it was written.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Spanned {
+    pub value: String,
+    pub range: Range,
+}
+
+// A KDL node as written, with source ranges for its name and values. The
+// outline is scanned leniently so that editor features keep working while a
+// document is being typed; `ir::ast::parse_kdl` remains the authority on
+// whether the document is valid.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OutlineNode {
+    pub name: Spanned,
+    pub args: Vec<Spanned>,
+    pub props: Vec<(Spanned, Spanned)>,
+    pub children: Vec<OutlineNode>,
+}
+
+impl OutlineNode {
+    pub fn first_arg(&self) -> Option<&Spanned> {
+        self.args.first()
+    }
+
+    pub fn prop(&self, key: &str) -> Option<&Spanned> {
+        self.props
+            .iter()
+            .find(|(name, _)| name.value == key)
+            .map(|(_, value)| value)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Word(Spanned),
+    Str(Spanned),
+    Eq,
+    LBrace,
+    RBrace,
+    Semi,
+    Newline,
+    SlashDash,
+}
+
+pub fn scan(text: &str) -> Vec<OutlineNode> {
+    let tokens = lex(text);
+    let mut pos = 0;
+    parse_nodes(&tokens, &mut pos, 0)
+}
+
+pub fn contains(range: &Range, position: Position) -> bool {
+    range.start <= position && position <= range.end
+}
+
+struct Cursor<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    line: u32,
+    // Columns count UTF-16 code units, as LSP positions do by default.
+    column: u32,
+}
+
+impl Cursor<'_> {
+    fn peek(&mut self) -> Option<char> {
+        self.chars.peek().copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let ch = self.chars.next()?;
+        if ch == '\n' {
+            self.line += 1;
+            self.column = 0;
+        } else {
+            self.column += ch.len_utf16() as u32;
+        }
+        Some(ch)
+    }
+
+    fn position(&self) -> Position {
+        Position::new(self.line, self.column)
+    }
+}
+
+fn lex(text: &str) -> Vec<Token> {
+    let mut cursor = Cursor {
+        chars: text.chars().peekable(),
+        line: 0,
+        column: 0,
+    };
+    let mut tokens = Vec::new();
+
+    while let Some(ch) = cursor.peek() {
+        let start = cursor.position();
+        match ch {
+            '\n' => {
+                cursor.bump();
+                tokens.push(Token::Newline);
+            }
+            '{' | '}' | ';' | '=' => {
+                cursor.bump();
+                tokens.push(match ch {
+                    '{' => Token::LBrace,
+                    '}' => Token::RBrace,
+                    ';' => Token::Semi,
+                    _ => Token::Eq,
+                });
+            }
+            '\\' => {
+                // Line continuation: skip through the next newline.
+                while let Some(ch) = cursor.bump() {
+                    if ch == '\n' {
+                        break;
+                    }
+                }
+            }
+            '/' => {
+                cursor.bump();
+                match cursor.peek() {
+                    Some('/') => {
+                        while !matches!(cursor.peek(), Some('\n') | None) {
+                            cursor.bump();
+                        }
+                    }
+                    Some('*') => skip_block_comment(&mut cursor),
+                    Some('-') => {
+                        cursor.bump();
+                        tokens.push(Token::SlashDash);
+                    }
+                    _ => {}
+                }
+            }
+            '"' => {
+                cursor.bump();
+                let value = lex_string(&mut cursor);
+                tokens.push(Token::Str(Spanned {
+                    value,
+                    range: Range::new(start, cursor.position()),
+                }));
+            }
+            c if c.is_whitespace() => {
+                cursor.bump();
+            }
+            _ => {
+                let mut value = String::new();
+                while let Some(ch) = cursor.peek() {
+                    if ch.is_whitespace() || "\\/(){}<>;[]=,\"".contains(ch) {
+                        break;
+                    }
+                    value.push(ch);
+                    cursor.bump();
+                }
+                if value.is_empty() {
+                    cursor.bump();
+                    continue;
+                }
+                tokens.push(Token::Word(Spanned {
+                    value,
+                    range: Range::new(start, cursor.position()),
+                }));
+            }
+        }
+    }
+
+    tokens
+}
+
+fn skip_block_comment(cursor: &mut Cursor) {
+    cursor.bump();
+    let mut depth = 1;
+    while depth > 0 {
+        match cursor.bump() {
+            None => return,
+            Some('*') if cursor.peek() == Some('/') => {
+                cursor.bump();
+                depth -= 1;
+            }
+            Some('/') if cursor.peek() == Some('*') => {
+                cursor.bump();
+                depth += 1;
+            }
+            Some(_) => {}
+        }
+    }
+}
+
+// Schema strings never span lines, so an unterminated string ends at the end
+// of its line instead of swallowing the rest of the document.
+fn lex_string(cursor: &mut Cursor) -> String {
+    let mut value = String::new();
+    while let Some(ch) = cursor.peek() {
+        match ch {
+            '\n' => break,
+            '"' => {
+                cursor.bump();
+                break;
+            }
+            '\\' => {
+                cursor.bump();
+                match cursor.bump() {
+                    Some('n') => value.push('\n'),
+                    Some('t') => value.push('\t'),
+                    Some('r') => value.push('\r'),
+                    Some(other) => value.push(other),
+                    None => break,
+                }
+            }
+            _ => {
+                value.push(ch);
+                cursor.bump();
+            }
+        }
+    }
+    value
+}
+
+fn parse_nodes(
+    tokens: &[Token],
+    pos: &mut usize,
+    depth: usize,
+) -> Vec<OutlineNode> {
+    let mut nodes = Vec::new();
+    while let Some(token) = tokens.get(*pos) {
+        match token {
+            Token::RBrace if depth > 0 => {
+                *pos += 1;
+                return nodes;
+            }
+            Token::Word(_) | Token::Str(_) => {
+                nodes.push(parse_node(tokens, pos, depth));
+            }
+            Token::SlashDash => {
+                *pos += 1;
+                if matches!(
+                    tokens.get(*pos),
+                    Some(Token::Word(_)) | Some(Token::Str(_))
+                ) {
+                    parse_node(tokens, pos, depth);
+                }
+            }
+            _ => *pos += 1,
+        }
+    }
+    nodes
+}
+
+fn parse_node(tokens: &[Token], pos: &mut usize, depth: usize) -> OutlineNode {
+    let name = spanned(&tokens[*pos]).expect("node name token");
+    *pos += 1;
+    let mut node = OutlineNode {
+        name,
+        args: Vec::new(),
+        props: Vec::new(),
+        children: Vec::new(),
+    };
+
+    let mut discard = false;
+    while let Some(token) = tokens.get(*pos) {
+        match token {
+            Token::Newline | Token::Semi => {
+                *pos += 1;
+                break;
+            }
+            Token::RBrace => break,
+            Token::SlashDash => {
+                *pos += 1;
+                discard = true;
+            }
+            Token::LBrace => {
+                *pos += 1;
+                let children = parse_nodes(tokens, pos, depth + 1);
+                if !discard {
+                    node.children = children;
+                }
+                break;
+            }
+            Token::Word(_) | Token::Str(_) => {
+                let value = spanned(token).expect("value token");
+                *pos += 1;
+                if tokens.get(*pos) == Some(&Token::Eq) {
+                    *pos += 1;
+                    let prop = tokens.get(*pos).and_then(spanned);
+                    if let Some(prop) = prop {
+                        *pos += 1;
+                        if !discard {
+                            node.props.push((value, prop));
+                        }
+                    }
+                } else if !discard {
+                    node.args.push(value);
+                }
+                discard = false;
+            }
+            Token::Eq => *pos += 1,
+        }
+    }
+
+    node
+}
+
+fn spanned(token: &Token) -> Option<Spanned> {
+    match token {
+        Token::Word(value) | Token::Str(value) => Some(value.clone()),
+        _ => None,
+    }
+}
@@ -0,0 +1,151 @@
+use schemaforge_lsp::analysis::{completion, definition, diagnostics, hover};
+use std::fs;
+use std::path::PathBuf;
+use tower_lsp::lsp_types::{
+    DiagnosticSeverity, HoverContents, Position, Range, Url,
+};
+
+fn fixture_dir() -> PathBuf {
+    let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    manifest_dir
+        .parent()
+        .expect("workspace root")
+        .join("schemaforge/tests/fixtures/include")
+}
+
+fn fixture(name: &str) -> (Url, String) {
+    let path = fixture_dir().join(name);
+    let text = fs::read_to_string(&path).expect("read fixture");
+    (Url::from_file_path(&path).expect("file url"), text)
+}
+
+// An unsaved document next to the include fixtures.
+fn editing(text: &str) -> (Url, String) {
+    let path = fixture_dir().join("editing.kdl");
+    (
+        Url::from_file_path(path).expect("file url"),
+        text.to_string(),
+    )
+}
+
+fn range(line: u32, start: u32, end: u32) -> Range {
+    Range::new(Position::new(line, start), Position::new(line, end))
+}
+
+fn hover_text(uri: &Url, text: &str, position: Position) -> String {
+    match hover(uri, text, position).expect("hover").contents {
+        HoverContents::Markup(markup) => markup.value,
+        other => panic!("unexpected hover contents: {:?}", other),
+    }
+}
+
+fn completion_labels(uri: &Url, text: &str, position: Position) -> Vec<String> {
+    completion(uri, text, position)
+        .into_iter()
+        .map(|item| item.label)
+        .collect()
+}
+
+#[test]
+fn valid_schema_with_includes_has_no_errors() {
+    let (uri, text) = fixture("main.kdl");
+    let diagnostics = diagnostics(&uri, &text);
+    assert!(
+        diagnostics
+            .iter()
+            .all(|d| d.severity != Some(DiagnosticSeverity::ERROR)),
+        "{:?}",
+        diagnostics
+    );
+}
+
+#[test]
+fn resolve_errors_point_at_the_quoted_name() {
+    let (uri, text) = editing(
+        "include \"common.kdl\"\n\nquery \"q\" table=\"user\" {\n    project \"name\"\n}\n",
+    );
+    let diagnostics = diagnostics(&uri, &text);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].severity, Some(DiagnosticSeverity::ERROR));
+    assert!(diagnostics[0].message.contains("'user'"));
+    assert_eq!(diagnostics[0].range, range(2, 16, 22));
+}
+
+#[test]
+fn parse_errors_are_reported_without_includes() {
+    let uri = Url::parse("untitled:schema.kdl").expect("url");
+    let diagnostics = diagnostics(&uri, "table \"t\" {\n    bogus \"x\"\n}\n");
+    assert_eq!(diagnostics.len(), 1);
+    assert!(diagnostics[0].message.starts_with("parse error: "));
+}
+
+#[test]
+fn goes_to_tables_and_fields_in_included_files() {
+    let (uri, text) = fixture("main.kdl");
+    let common = Url::from_file_path(fixture_dir().join("common.kdl"))
+        .expect("file url");
+
+    let table = definition(&uri, &text, Position::new(3, 27)).expect("table");
+    assert_eq!(table.uri, common);
+    assert_eq!(table.range, range(0, 6, 13));
+
+    let field = definition(&uri, &text, Position::new(5, 12)).expect("field");
+    assert_eq!(field.uri, common);
+    assert_eq!(field.range, range(2, 8, 14));
+}
+
+#[test]
+fn qualifies_module_tables() {
+    let (uri, text) = fixture("nested/billing.kdl");
+
+    // `proc ... table="invoice"` inside `module "billing"`.
+    let table = definition(&uri, &text, Position::new(8, 33)).expect("table");
+    assert_eq!(table.uri, uri);
+    assert_eq!(table.range, range(3, 8, 17));
+
+    // `query "list_invoices" table="billing.invoice"` outside the module.
+    let table = definition(&uri, &text, Position::new(18, 33)).expect("table");
+    assert_eq!(table.range, range(3, 8, 17));
+
+    assert_eq!(
+        hover_text(&uri, &text, Position::new(10, 12)),
+        "```\nbilling.invoice.user_id: i64\n```"
+    );
+}
+
+#[test]
+fn hover_shows_field_types_and_attributes() {
+    let (uri, text) = editing(
+        "table \"people\" {\n    field \"id\" type=\"i64\" key=true auto-increment=true\n    field \"name\" type=\"text\" default=\"anon\"\n}\n",
+    );
+    assert_eq!(
+        hover_text(&uri, &text, Position::new(1, 12)),
+        "```\npeople.id: i64\n```\n\nprimary key, auto-increment"
+    );
+    assert_eq!(
+        hover_text(&uri, &text, Position::new(0, 9)),
+        "```\ntable people\n```\n\n- `id`: `i64`\n- `name`: `text`"
+    );
+}
+
+#[test]
+fn completes_tables_and_columns() {
+    let (uri, text) = editing(
+        "include \"nested/billing.kdl\"\n\nmixin \"audit\" {\n    field \"created\" type=\"i64\"\n}\n\ntable \"notes\" {\n    field \"body\" type=\"text\"\n    use \"audit\"\n}\n\nquery \"q\" table=\"\" {\n    project \"\"\n}\n",
+    );
+
+    assert_eq!(
+        completion_labels(&uri, &text, Position::new(11, 17)),
+        vec!["notes", "billing.invoice", "users"]
+    );
+
+    let text = text.replace("table=\"\"", "table=\"notes\"");
+    assert_eq!(
+        completion_labels(&uri, &text, Position::new(12, 13)),
+        vec!["body", "created"]
+    );
+    assert_eq!(
+        completion_labels(&uri, &text, Position::new(8, 10)),
+        vec!["audit"]
+    );
+}
@@ -0,0 +1,66 @@
+use std::io::{BufRead, BufReader, Read, Write};
+use std::process::{ChildStdout, Command, Stdio};
+
+fn send(stdin: &mut impl Write, body: &str) {
+    write!(stdin, "Content-Length: {}\r\n\r\n{}", body.len(), body)
+        .expect("write message");
+    stdin.flush().expect("flush message");
+}
+
+fn receive(stdout: &mut BufReader<ChildStdout>) -> String {
+    let mut length = 0;
+    loop {
+        let mut header = String::new();
+        stdout.read_line(&mut header).expect("read header");
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some(value) = header.strip_prefix("Content-Length: ") {
+            length = value.parse().expect("content length");
+        }
+    }
+    let mut body = vec![0; length];
+    stdout.read_exact(&mut body).expect("read body");
+    String::from_utf8(body).expect("utf-8 body")
+}
+
+#[test]
+fn server_answers_initialize_and_shuts_down() {
+    let binary = env!("CARGO_BIN_EXE_schemaforge-lsp");
+    let mut child = Command::new(binary)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("spawn schemaforge-lsp");
+    let mut stdin = child.stdin.take().expect("stdin");
+    let mut stdout = BufReader::new(child.stdout.take().expect("stdout"));
+
+    send(
+        &mut stdin,
+        r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"capabilities":{}}}"#,
+    );
+    let initialized = receive(&mut stdout);
+    assert!(initialized.contains(r#""definitionProvider":true"#));
+    assert!(initialized.contains(r#""hoverProvider":true"#));
+    assert!(initialized.contains(r#""name":"schemaforge-lsp""#));
+
+    send(
+        &mut stdin,
+        r#"{"jsonrpc":"2.0","method":"initialized","params":{}}"#,
+    );
+    send(
+        &mut stdin,
+        r#"{"jsonrpc":"2.0","id":2,"method":"shutdown"}"#,
+    );
+    // The server may log before answering.
+    let mut response = receive(&mut stdout);
+    while !response.contains(r#""id":2"#) {
+        response = receive(&mut stdout);
+    }
+    assert!(response.contains(r#""result":null"#), "{}", response);
+
+    send(&mut stdin, r#"{"jsonrpc":"2.0","method":"exit"}"#);
+    drop(stdin);
+    assert!(child.wait().expect("wait for server").success());
+}
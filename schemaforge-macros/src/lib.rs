@@ -0,0 +1,136 @@
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use schemaforge::build::render_module_with;
+use schemaforge::ir::ast::parse_kdl;
+use schemaforge::load::load_schema;
+use schemaforge::passes;
+use std::path::{Path, PathBuf};
+use syn::parse::{Parse, ParseStream};
+use syn::{Ident, LitBool, LitStr, Token};
+
+/// Expands an inline schema, or a schema file, to the `Db` module that
+/// `schemaforge-cli build` generates. The expansion needs the `rusqlite` crate
+/// and defines `Db` and `Error` alongside private helpers and imports, so
+/// invoke it inside its own module:
+///
+/// ```ignore
+/// mod db {
+///     schemaforge_macros::schemaforge!(r#"
+///         table "people" {
+///             field "id" type="i64" key=true auto-increment=true
+///             field "name" type="text"
+///         }
+///     "#);
+/// }
+///
+/// mod app {
+///     // Relative to the invoking crate's manifest directory.
+///     schemaforge_macros::schemaforge!(path = "schema/app.kdl");
+/// }
+///
+/// mod shared {
+///     // Also generates `AsyncDb`; needs the `futures-channel` crate.
+///     schemaforge_macros::schemaforge!(path = "schema/app.kdl", async_api = true);
+/// }
+/// ```
+///
+/// Schema errors are reported as `compile_error!` at the call site:
+///
+/// ```compile_fail
+/// schemaforge_macros::schemaforge!(r#"
+///     query "names" table="missing" {
+///         project "name"
+///     }
+/// "#);
+/// ```
+#[proc_macro]
+pub fn schemaforge(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as Input);
+    match expand(input) {
+        Ok(tokens) => tokens,
+        Err(message) => syn::Error::new(Span::call_site(), message)
+            .to_compile_error()
+            .into(),
+    }
+}
+
+struct Input {
+    source: Source,
+    async_api: bool,
+}
+
+enum Source {
+    Inline(LitStr),
+    Path(LitStr),
+}
+
+impl Parse for Input {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let source = if input.peek(LitStr) {
+            Source::Inline(input.parse()?)
+        } else {
+            let key = input.parse::<Ident>()?;
+            if key != "path" {
+                return Err(syn::Error::new(
+                    key.span(),
+                    "expected a schema string literal or `path = \"...\"`",
+                ));
+            }
+            input.parse::<Token![=]>()?;
+            Source::Path(input.parse()?)
+        };
+
+        let mut async_api = false;
+        if input.parse::<Option<Token![,]>>()?.is_some() && !input.is_empty() {
+            let key = input.parse::<Ident>()?;
+            if key != "async_api" {
+                return Err(syn::Error::new(
+                    key.span(),
+                    "expected `async_api = true` or `async_api = false`",
+                ));
+            }
+            input.parse::<Token![=]>()?;
+            async_api = input.parse::<LitBool>()?.value;
+            input.parse::<Option<Token![,]>>()?;
+        }
+        Ok(Input { source, async_api })
+    }
+}
+
+fn expand(input: Input) -> Result<TokenStream, String> {
+    let (ast, files) = match input.source {
+        Source::Inline(src) => (
+            parse_kdl(&src.value()).map_err(|err| err.to_string())?,
+            Vec::new(),
+        ),
+        Source::Path(path) => {
+            let manifest_dir = std::env::var("CARGO_MANIFEST_DIR")
+                .map_err(|_| "CARGO_MANIFEST_DIR is not set".to_string())?;
+            let path = Path::new(&manifest_dir).join(path.value());
+            let loaded = load_schema(&path).map_err(|err| err.to_string())?;
+            (loaded.ast, loaded.files)
+        }
+    };
+
+    let schema = passes::run_frontend(&ast).map_err(|err| err.to_string())?;
+    let mut module = render_module_with(&schema, input.async_api)
+        .map_err(|err| err.to_string())?;
+    module.push_str(&track_files(&files));
+    module
+        .parse()
+        .map_err(|err| format!("generated code does not parse: {}", err))
+}
+
+// Proc macros cannot emit `cargo:rerun-if-changed`, but rustc rebuilds the
+// invoking crate whenever a file read by `include_str!` changes.
+fn track_files(files: &[PathBuf]) -> String {
+    files
+        .iter()
+        .map(|file| {
+            format!(
+                "const _: &str = include_str!({:?});\n",
+                file.display().to_string()
+            )
+        })
+        .collect()
+}
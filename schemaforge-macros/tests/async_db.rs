@@ -0,0 +1,105 @@
+use std::sync::Arc;
+
+mod people {
+    schemaforge_macros::schemaforge!(
+        r#"
+        table "people" {
+            field "id" type="i64" key=true auto-increment=true
+            field "name" type="text" {
+                check "len(name) > 0" name="name_not_empty"
+            }
+        }
+
+        proc "add_person" table="people" {
+            param "name" type="text"
+        }
+
+        query "names" table="people" {
+            project "name"
+        }
+        "#,
+        async_api = true
+    );
+}
+
+// An enum named `job` must not collide with the generated job queue type.
+mod staff {
+    schemaforge_macros::schemaforge!(
+        r#"
+        enum "job" {
+            variant "cook"
+            variant "waiter"
+        }
+
+        table "staff" {
+            field "name" type="text"
+            field "job" type="job"
+        }
+
+        proc "hire" table="staff" {
+            param "name" type="text"
+            param "job" type="job"
+        }
+
+        query "jobs" table="staff" {
+            project "job"
+        }
+        "#,
+        async_api = true
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn async_db_is_shared_across_tasks() {
+    let db = Arc::new(people::AsyncDb::new().await.expect("open db"));
+
+    let tasks = (0..4)
+        .map(|task| {
+            let db = Arc::clone(&db);
+            tokio::spawn(async move {
+                db.add_person(format!("person {}", task)).await
+            })
+        })
+        .collect::<Vec<_>>();
+    for task in tasks {
+        task.await.expect("join").expect("insert");
+    }
+
+    let rows = (0..2).map(|row| people::AddPersonRow {
+        name: format!("bulk {}", row),
+    });
+    assert_eq!(db.add_person_many(rows).await.expect("insert rows"), 2);
+
+    let mut names = db.names().await.expect("query");
+    names.sort();
+    assert_eq!(names.len(), 6);
+    assert_eq!(names[0], ("bulk 0".to_string(),));
+}
+
+#[tokio::test]
+async fn async_db_returns_typed_errors() {
+    let db = people::AsyncDb::new().await.expect("open db");
+    match db.add_person(String::new()).await {
+        Err(people::Error::Constraint { check, .. }) => {
+            assert_eq!(check.as_deref(), Some("name_not_empty"));
+        }
+        other => panic!("expected a check violation, got {:?}", other),
+    }
+}
+
+#[test]
+fn async_errors_include_closed() {
+    assert_eq!(
+        people::Error::Closed.to_string(),
+        "database thread has stopped"
+    );
+}
+
+#[tokio::test]
+async fn enum_named_job_compiles() {
+    let db = staff::AsyncDb::new().await.expect("open db");
+    db.hire("ada".to_string(), staff::Job::Cook)
+        .await
+        .expect("insert");
+    assert_eq!(db.jobs().await.expect("query"), vec![(staff::Job::Cook,)]);
+}
@@ -0,0 +1,44 @@
+mod people {
+    schemaforge_macros::schemaforge!(
+        r#"
+        table "people" {
+            field "id" type="i64" key=true
+            field "name" type="text" {
+                check "len(name) > 0" name="name_not_empty"
+            }
+        }
+
+        proc "add_person" table="people" {
+            param "id" type="i64"
+            param "name" type="text"
+        }
+        "#
+    );
+}
+
+#[test]
+fn bulk_inserts_run_in_one_transaction() {
+    let mut db = people::Db::new().expect("open db");
+    let rows = (1..=3).map(|id| people::AddPersonRow {
+        id,
+        name: format!("person {}", id),
+    });
+    assert_eq!(db.add_person_many(rows).expect("insert rows"), 3);
+
+    // The empty name fails its check, so the whole batch is rolled back.
+    let err = db
+        .add_person_many(vec![
+            people::AddPersonRow {
+                id: 4,
+                name: "ada".to_string(),
+            },
+            people::AddPersonRow {
+                id: 5,
+                name: String::new(),
+            },
+        ])
+        .expect_err("check violation");
+    assert!(matches!(err, people::Error::Constraint { .. }));
+    db.add_person(4, "ada".to_string())
+        .expect("id 4 is still free");
+}
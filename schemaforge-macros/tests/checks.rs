@@ -0,0 +1,29 @@
+mod people {
+    schemaforge_macros::schemaforge!(
+        path = "../schemaforge/testdata/passes/resolve/007-check.in.kdl"
+    );
+}
+
+#[test]
+fn check_violations_name_the_failed_check() {
+    let mut db = people::Db::new().expect("open db");
+    db.add_person(1, "ada".to_string(), 36).expect("insert");
+
+    for (name, age, expected) in [
+        ("", 36, "name_not_empty"),
+        ("grace", 200, "people_age_check_1"),
+        ("root", 12, "people_check_1"),
+    ] {
+        match db.add_person(2, name.to_string(), age) {
+            Err(people::Error::Constraint { table, check, .. }) => {
+                assert_eq!(table, "people");
+                assert_eq!(check.as_deref(), Some(expected));
+            }
+            other => panic!("expected check {}, got {:?}", expected, other),
+        }
+    }
+    assert_eq!(
+        db.people_names().expect("query"),
+        vec![("ada".to_string(),)]
+    );
+}
@@ -0,0 +1,35 @@
+mod tickets {
+    schemaforge_macros::schemaforge!(
+        path = "../schemaforge/testdata/passes/resolve/009-defaults.in.kdl"
+    );
+}
+
+use tickets::Status;
+
+#[test]
+fn omitted_and_none_params_use_column_defaults() {
+    let mut db = tickets::Db::new().expect("open db");
+
+    // `open_ticket` does not name `owner` or `status`; `points` is `None`.
+    assert_eq!(db.open_ticket("a".to_string(), None).expect("insert"), 1);
+    assert_eq!(db.open_ticket("b".to_string(), Some(3)).expect("insert"), 2);
+    // `None` never stores NULL in a defaulted column.
+    db.assign_ticket("c".to_string(), None, None)
+        .expect("insert");
+    db.assign_ticket(
+        "d".to_string(),
+        Some("ada".to_string()),
+        Some(Status::Closed),
+    )
+    .expect("insert");
+
+    assert_eq!(
+        db.ticket_rows().expect("query"),
+        vec![
+            (1, "a".to_string(), Status::Open, -1, "nobody".to_string()),
+            (2, "b".to_string(), Status::Open, 3, "nobody".to_string()),
+            (3, "c".to_string(), Status::Open, -1, "nobody".to_string()),
+            (4, "d".to_string(), Status::Closed, -1, "ada".to_string()),
+        ]
+    );
+}
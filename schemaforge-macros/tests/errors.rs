@@ -0,0 +1,47 @@
+mod people {
+    schemaforge_macros::schemaforge!(
+        r#"
+        table "people" {
+            field "id" type="i64" key=true
+            field "name" type="text"
+        }
+
+        proc "add_person" table="people" {
+            param "id" type="i64"
+            param "name" type="text"
+        }
+        "#
+    );
+}
+
+#[test]
+fn constraint_failures_are_typed_errors() {
+    let mut db = people::Db::new().expect("open db");
+    db.add_person(1, "ada".to_string()).expect("insert");
+
+    match db.add_person(1, "grace".to_string()) {
+        Err(people::Error::Constraint {
+            table,
+            check,
+            message,
+        }) => {
+            assert_eq!(table, "people");
+            assert_eq!(check, None);
+            assert!(message.contains("UNIQUE"), "{}", message);
+        }
+        other => panic!("expected a key violation, got {:?}", other),
+    }
+}
+
+// Without `async_api` there is no `Closed` variant, so this match needs no
+// wildcard arm.
+#[test]
+fn sync_errors_have_no_closed_variant() {
+    let kind = match people::Error::NotFound {
+        people::Error::Sqlite(_) => "sqlite",
+        people::Error::Constraint { .. } => "constraint",
+        people::Error::NotFound => "not found",
+        people::Error::Decode { .. } => "decode",
+    };
+    assert_eq!(kind, "not found");
+}
@@ -0,0 +1,45 @@
+mod inline {
+    schemaforge_macros::schemaforge!(
+        r#"
+        table "people" {
+            field "id" type="i64" key=true auto-increment=true
+            field "name" type="text"
+        }
+
+        proc "add_person" table="people" {
+            param "name" type="text"
+        }
+
+        query "names" table="people" {
+            project "name"
+        }
+        "#
+    );
+}
+
+mod from_path {
+    schemaforge_macros::schemaforge!(
+        path = "../schemaforge/tests/fixtures/spike/spike.in.kdl"
+    );
+}
+
+#[test]
+fn inline_schema_expands_to_db() {
+    let mut db = inline::Db::new().expect("open db");
+    assert_eq!(db.add_person("ada".to_string()).expect("insert"), 1);
+    assert_eq!(db.add_person("grace".to_string()).expect("insert"), 2);
+    assert_eq!(
+        db.names().expect("query"),
+        vec![("ada".to_string(),), ("grace".to_string(),)]
+    );
+}
+
+#[test]
+fn schema_path_expands_to_db() {
+    let mut db = from_path::Db::new().expect("open db");
+    db.insert_person(7, "ada".to_string(), 36).expect("insert");
+    assert_eq!(
+        db.list_names_and_ids().expect("query"),
+        vec![("ada".to_string(), 7)]
+    );
+}
@@ -0,0 +1,115 @@
+// Compares the generated `Db`, which reuses cached statements, with the
+// same SQL prepared on every call, and with `<proc>_many`. It is a benchmark,
+// so it only runs on request:
+//
+//     cargo test -p schemaforge-macros --test statement_cache -- \
+//         --ignored --nocapture
+use rusqlite::{params, Connection};
+use std::time::{Duration, Instant};
+
+mod people {
+    schemaforge_macros::schemaforge!(
+        r#"
+        table "people" {
+            field "id" type="i64" key=true auto-increment=true
+            field "name" type="text"
+        }
+
+        proc "add_person" table="people" {
+            param "name" type="text"
+        }
+
+        query "names" table="people" {
+            project "name"
+        }
+        "#
+    );
+}
+
+const ROWS: usize = 2_000;
+const QUERIES: usize = 200;
+
+fn timed(run: impl FnOnce()) -> Duration {
+    let start = Instant::now();
+    run();
+    start.elapsed()
+}
+
+fn uncached_db() -> Connection {
+    let conn = Connection::open_in_memory().expect("open db");
+    conn.execute_batch(
+        "CREATE TABLE \"people\" \
+         (\"id\" INTEGER PRIMARY KEY AUTOINCREMENT, \"name\" TEXT);",
+    )
+    .expect("create table");
+    conn
+}
+
+fn uncached_insert(conn: &Connection, name: String) {
+    conn.execute(
+        "INSERT INTO \"people\" (\"name\") VALUES (?1)",
+        params![name],
+    )
+    .expect("insert");
+}
+
+// Queries are timed against small tables so that statement preparation,
+// not reading rows, dominates.
+#[test]
+#[ignore = "benchmark; run with --ignored --nocapture"]
+fn cached_statements_throughput() {
+    let conn = uncached_db();
+    let uncached_inserts = timed(|| {
+        for row in 0..ROWS {
+            uncached_insert(&conn, format!("person {}", row));
+        }
+    });
+    let small = uncached_db();
+    for row in 0..10 {
+        uncached_insert(&small, format!("person {}", row));
+    }
+    let uncached_queries = timed(|| {
+        for _ in 0..QUERIES {
+            let mut stmt = small
+                .prepare("SELECT \"name\" FROM \"people\"")
+                .expect("prepare");
+            let rows = stmt
+                .query_map([], |row| row.get::<_, String>(0))
+                .expect("query");
+            assert_eq!(rows.count(), 10);
+        }
+    });
+
+    let mut db = people::Db::new().expect("open db");
+    let cached_inserts = timed(|| {
+        for row in 0..ROWS {
+            db.add_person(format!("person {}", row)).expect("insert");
+        }
+    });
+    let mut bulk = people::Db::new().expect("open db");
+    let bulk_inserts = timed(|| {
+        let rows = (0..ROWS).map(|row| people::AddPersonRow {
+            name: format!("person {}", row),
+        });
+        assert_eq!(bulk.add_person_many(rows).expect("insert"), ROWS);
+    });
+    let mut small = people::Db::new().expect("open db");
+    for row in 0..10 {
+        small.add_person(format!("person {}", row)).expect("insert");
+    }
+    let cached_queries = timed(|| {
+        for _ in 0..QUERIES {
+            assert_eq!(small.names().expect("query").len(), 10);
+        }
+    });
+
+    println!(
+        "{} inserts: prepared per call {:?}, cached {:?}, one transaction {:?}",
+        ROWS, uncached_inserts, cached_inserts, bulk_inserts
+    );
+    println!(
+        "{} queries: prepared per call {:?}, cached {:?}",
+        QUERIES, uncached_queries, cached_queries
+    );
+    assert_eq!(db.names().expect("query").len(), ROWS);
+}
@@ -1,5 +1,8 @@
 use crate::error::Error;
-use crate::ir::schema::{ProcIr, ResolvedSchema, TableIr};
+use crate::ir::expr::{enum_comparison, BinaryOp, Expr, UnaryOp};
+use crate::ir::schema::{
+    CheckIr, EnumIr, FieldIr, ProcIr, ResolvedSchema, TableIr,
+};
 use crate::plan::{ColumnId, Plan};
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -16,6 +19,9 @@ pub fn compile_plan_to_sql(
         Plan::TableScan { .. } => Err(Error::Pass(
             "unsupported plan shape for SQLite backend: bare TableScan".into(),
         )),
+        Plan::Filter { .. } => Err(Error::Pass(
+            "unsupported plan shape for SQLite backend: bare Filter".into(),
+        )),
         Plan::Project { input, columns } => {
             if columns.is_empty() {
                 return Err(Error::Pass(
@@ -24,8 +30,17 @@ pub fn compile_plan_to_sql(
                 ));
             }
 
-            let table_id = match input.as_ref() {
-                Plan::TableScan { table } => *table,
+            let (table_id, predicate) = match input.as_ref() {
+                Plan::TableScan { table } => (*table, None),
+                Plan::Filter { input, predicate } => match input.as_ref() {
+                    Plan::TableScan { table } => (*table, Some(predicate)),
+                    _ => {
+                        return Err(Error::Pass(
+                            "unsupported plan shape for SQLite backend: expected Filter(TableScan)"
+                                .into(),
+                        ))
+                    }
+                },
                 _ => {
                     return Err(Error::Pass(
                         "unsupported plan shape for SQLite backend: expected Project(TableScan)"
@@ -59,26 +74,69 @@ pub fn compile_plan_to_sql(
                 selected_columns.push(quote_ident(&column.name));
             }
 
+            let mut sql = format!(
+                "SELECT {} FROM {}",
+                selected_columns.join(", "),
+                quote_ident(&table.name)
+            );
+            if let Some(predicate) = predicate {
+                sql.push_str(&format!(
+                    " WHERE {}",
+                    compile_expr_sql(predicate, table, schema)
+                ));
+            }
+
             Ok(SqlQuery {
-                sql: format!(
-                    "SELECT {} FROM {}",
-                    selected_columns.join(", "),
-                    quote_ident(&table.name)
-                ),
+                sql,
                 result_columns: columns.clone(),
             })
         }
     }
 }
 
-pub fn compile_create_table_sql(table: &TableIr) -> Result<String, Error> {
+pub fn compile_create_table_sql(
+    table: &TableIr,
+    schema: &ResolvedSchema,
+) -> Result<String, Error> {
     let mut columns = Vec::with_capacity(table.fields.len());
     for field in &table.fields {
-        columns.push(format!(
+        let mut column = format!(
             "{} {}",
             quote_ident(&field.name),
-            sqlite_type_name(&field.ty)?
-        ));
+            column_type_name(&field.ty, schema)?
+        );
+        if field.key {
+            column.push_str(" PRIMARY KEY");
+        }
+        if field.auto_increment {
+            column.push_str(" AUTOINCREMENT");
+        }
+        if let Some(default) = compile_default_sql(field, table, schema)? {
+            column.push_str(&format!(" DEFAULT {}", default));
+        }
+        if let Some(computed) = &field.computed {
+            column.push_str(&format!(
+                " GENERATED ALWAYS AS ({}) STORED",
+                compile_expr_sql(computed, table, schema)
+            ));
+        }
+        if let Some(enum_def) = schema.enum_type(&field.ty) {
+            column.push_str(&format!(
+                " CHECK ({} IN ({}))",
+                quote_ident(&field.name),
+                enum_variant_values(enum_def).join(", ")
+            ));
+        }
+        for check in &field.checks {
+            column.push_str(&format!(
+                " {}",
+                compile_check_sql(check, table, schema)
+            ));
+        }
+        columns.push(column);
+    }
+    for check in &table.checks {
+        columns.push(compile_check_sql(check, table, schema));
     }
 
     Ok(format!(
@@ -88,6 +146,147 @@ pub fn compile_create_table_sql(table: &TableIr) -> Result<String, Error> {
     ))
 }
 
+pub fn compile_default_sql(
+    field: &FieldIr,
+    table: &TableIr,
+    schema: &ResolvedSchema,
+) -> Result<Option<String>, Error> {
+    let default = match &field.default {
+        Some(default) => default,
+        None => return Ok(None),
+    };
+
+    match (schema.enum_type(&field.ty), default) {
+        (Some(enum_def), Expr::Text(value)) if enum_def.repr == "i64" => {
+            let index = enum_def
+                .variants
+                .iter()
+                .position(|variant| variant == value)
+                .ok_or_else(|| {
+                    Error::Pass(format!(
+                        "default '{}' for field '{}' is not a variant of enum '{}'",
+                        value, field.name, enum_def.name
+                    ))
+                })?;
+            Ok(Some(index.to_string()))
+        }
+        _ => Ok(Some(compile_expr_sql(default, table, schema))),
+    }
+}
+
+pub fn compile_check_sql(
+    check: &CheckIr,
+    table: &TableIr,
+    schema: &ResolvedSchema,
+) -> String {
+    format!(
+        "CONSTRAINT {} CHECK ({})",
+        quote_ident(&check.name),
+        compile_expr_sql(&check.expr, table, schema)
+    )
+}
+
+pub fn compile_expr_sql(
+    expr: &Expr,
+    table: &TableIr,
+    schema: &ResolvedSchema,
+) -> String {
+    match expr {
+        Expr::Column(name) => quote_ident(name),
+        Expr::Integer(value) => value.to_string(),
+        Expr::Text(value) => quote_literal(value),
+        Expr::Bool(true) => "1".to_string(),
+        Expr::Bool(false) => "0".to_string(),
+        Expr::Null => "NULL".to_string(),
+        // The operand is always parenthesized: `--` starts a SQL comment.
+        Expr::Unary {
+            op: UnaryOp::Neg,
+            operand,
+        } => format!("-({})", compile_expr_sql(operand, table, schema)),
+        Expr::Unary {
+            op: UnaryOp::Not,
+            operand,
+        } => format!("NOT {}", compile_operand_sql(operand, table, schema)),
+        Expr::Binary { op, left, right } => {
+            let sql_op = match op {
+                BinaryOp::Or => "OR",
+                BinaryOp::And => "AND",
+                BinaryOp::Ne => "<>",
+                other => other.symbol(),
+            };
+            let mut left_sql = compile_operand_sql(left, table, schema);
+            let mut right_sql = compile_operand_sql(right, table, schema);
+            // Enums stored as integers compare against the variant index.
+            if let Some((column, value)) = enum_comparison(left, right) {
+                if let Some(index) = enum_index(column, value, table, schema) {
+                    if matches!(left.as_ref(), Expr::Text(_)) {
+                        left_sql = index;
+                    } else {
+                        right_sql = index;
+                    }
+                }
+            }
+            format!("{} {} {}", left_sql, sql_op, right_sql)
+        }
+        Expr::IsNull { operand, negated } => format!(
+            "{} {}",
+            compile_operand_sql(operand, table, schema),
+            if *negated { "IS NOT NULL" } else { "IS NULL" }
+        ),
+        Expr::Call { function, args } => {
+            let function = match function.as_str() {
+                "len" => "length",
+                other => other,
+            };
+            let args = args
+                .iter()
+                .map(|arg| compile_expr_sql(arg, table, schema))
+                .collect::<Vec<_>>();
+            format!("{}({})", function, args.join(", "))
+        }
+    }
+}
+
+fn compile_operand_sql(
+    expr: &Expr,
+    table: &TableIr,
+    schema: &ResolvedSchema,
+) -> String {
+    match expr {
+        Expr::Unary {
+            op: UnaryOp::Not, ..
+        }
+        | Expr::Binary { .. }
+        | Expr::IsNull { .. } => {
+            format!("({})", compile_expr_sql(expr, table, schema))
+        }
+        _ => compile_expr_sql(expr, table, schema),
+    }
+}
+
+fn enum_index(
+    column: &str,
+    value: &str,
+    table: &TableIr,
+    schema: &ResolvedSchema,
+) -> Option<String> {
+    let field = table.fields.iter().find(|field| field.name == column)?;
+    let enum_def = schema.enum_type(&field.ty)?;
+    if enum_def.repr != "i64" {
+        return None;
+    }
+    enum_def
+        .variants
+        .iter()
+        .position(|variant| variant == value)
+        .map(|index| index.to_string())
+}
+
+// Columns a proc does not name are left out of the INSERT, so SQLite fills
+// them from their defaults. A param naming a defaulted column is optional
+// instead: it binds NULL when omitted and COALESCE substitutes the default.
+// Generated code treats every column as non-null (queries decode `T`, not
+// `Option<T>`), so a defaulted column can never be set to NULL by a proc.
 pub fn compile_insert_proc_sql(
     proc_def: &ProcIr,
     schema: &ResolvedSchema,
@@ -125,7 +324,14 @@ pub fn compile_insert_proc_sql(
         })?;
 
         column_names.push(quote_ident(&column.name));
-        placeholders.push(format!("?{}", index + 1));
+        match compile_default_sql(column, table, schema)? {
+            Some(default) => placeholders.push(format!(
+                "COALESCE(?{}, {})",
+                index + 1,
+                default
+            )),
+            None => placeholders.push(format!("?{}", index + 1)),
+        }
     }
 
     Ok(format!(
@@ -136,6 +342,28 @@ pub fn compile_insert_proc_sql(
     ))
 }
 
+pub fn column_type_name(
+    type_name: &str,
+    schema: &ResolvedSchema,
+) -> Result<&'static str, Error> {
+    match schema.enum_type(type_name) {
+        Some(enum_def) => sqlite_type_name(&enum_def.repr),
+        None => sqlite_type_name(type_name),
+    }
+}
+
+pub fn enum_variant_values(enum_def: &EnumIr) -> Vec<String> {
+    enum_def
+        .variants
+        .iter()
+        .enumerate()
+        .map(|(index, variant)| match enum_def.repr.as_str() {
+            "i64" => index.to_string(),
+            _ => quote_literal(variant),
+        })
+        .collect()
+}
+
 pub fn sqlite_type_name(type_name: &str) -> Result<&'static str, Error> {
     match type_name {
         "i64" => Ok("INTEGER"),
@@ -150,3 +378,7 @@ pub fn sqlite_type_name(type_name: &str) -> Result<&'static str, Error> {
 pub fn quote_ident(value: &str) -> String {
     format!("\"{}\"", value.replace('"', "\"\""))
 }
+
+pub fn quote_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
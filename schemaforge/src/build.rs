@@ -2,44 +2,401 @@ use crate::backend::sqlite::{
     compile_create_table_sql, compile_insert_proc_sql, compile_plan_to_sql,
 };
 use crate::error::Error;
-use crate::ir;
-use crate::ir::schema::{ProcIr, QueryIr, ResolvedSchema};
+use crate::ir::schema::{EnumIr, ProcIr, QueryIr, ResolvedSchema, TableIr};
+use crate::load::load_schema;
 use crate::lower::{lower_queries, LoweredQuery};
 use crate::plan::ColumnId;
 use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 
+// Written into every generated crate; `build` only writes into an existing,
+// non-empty output directory that contains it.
+const MARKER_FILE: &str = ".schemaforge-generated";
+
+// Where and under which names `build_with` writes the generated crate.
+// Unset fields fall back to `target/schemaforge-out/<stem>`, a
+// `schemaforge_generated_<stem>` library crate, and `Db` at the crate root.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BuildOptions {
+    pub output_dir: Option<PathBuf>,
+    pub crate_name: Option<String>,
+    // When set, `Db` lives in `src/<module_name>.rs` and is reached as
+    // `<crate>::<module_name>::Db`.
+    pub module_name: Option<String>,
+    // Also generate `src/main.rs`, a demo binary exercising the first proc
+    // and query.
+    pub demo: bool,
+    pub rusqlite: RusqliteDependency,
+    // Write only `<module_name>.rs` (default `<stem>.rs`) for the caller to
+    // `mod` or `include!` into an existing crate, with no `Cargo.toml`.
+    pub module_only: bool,
+    // Also generate `AsyncDb`, which needs the `futures-channel` crate.
+    pub async_api: bool,
+}
+
+pub const DEFAULT_RUSQLITE_VERSION: &str = "0.31";
+
+// How the generated `Cargo.toml` depends on rusqlite.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RusqliteDependency {
+    Version {
+        version: String,
+        features: Vec<String>,
+    },
+    // Inherits the dependency from the surrounding workspace, which the
+    // generated crate then has to be a member of.
+    Workspace {
+        features: Vec<String>,
+    },
+}
+
+impl Default for RusqliteDependency {
+    fn default() -> Self {
+        RusqliteDependency::Version {
+            version: DEFAULT_RUSQLITE_VERSION.to_string(),
+            features: Vec::new(),
+        }
+    }
+}
+
 pub fn build(input: &Path) -> Result<PathBuf, Error> {
-    let input_text = fs::read_to_string(input)?;
-    let ast = ir::ast::parse_kdl(&input_text)?;
-    let schema = crate::passes::resolve::run(&ast)?;
-    let lowered = lower_queries(&schema)?;
+    build_with(input, &BuildOptions::default())
+}
 
-    let output_dir = output_dir(input);
-    if output_dir.exists() {
-        fs::remove_dir_all(&output_dir)?;
+pub fn build_with(
+    input: &Path,
+    options: &BuildOptions,
+) -> Result<PathBuf, Error> {
+    let crate_name = match &options.crate_name {
+        Some(name) => validate_crate_name(name)?,
+        None => crate_name(input),
+    };
+    let module_name = match &options.module_name {
+        Some(name) => Some(validate_module_name(name)?),
+        None => None,
+    };
+    validate_rusqlite(&options.rusqlite)?;
+    if options.module_only {
+        let unused = [
+            (options.crate_name.is_some(), "a crate name"),
+            (options.demo, "a demo binary"),
+            (
+                options.rusqlite != RusqliteDependency::default(),
+                "rusqlite dependency settings",
+            ),
+        ];
+        if let Some((_, what)) = unused.iter().find(|(set, _)| *set) {
+            return Err(Error::Pass(format!(
+                "module-only output has no Cargo.toml, so {} cannot be applied",
+                what
+            )));
+        }
     }
-    fs::create_dir_all(output_dir.join("src"))?;
 
-    let crate_name = crate_name(input);
+    let loaded = load_schema(input)?;
+    let output_dir = options
+        .output_dir
+        .clone()
+        .unwrap_or_else(|| output_dir(input));
+    let previous = read_manifest(&output_dir)?;
 
-    fs::write(
-        output_dir.join("Cargo.toml"),
-        render_cargo_toml(&crate_name),
-    )?;
-    fs::write(
-        output_dir.join("src/lib.rs"),
-        render_lib_rs(&schema, &lowered)?,
-    )?;
-    fs::write(
-        output_dir.join("src/main.rs"),
-        render_main_rs(&schema, &lowered, &crate_name)?,
+    let hash = input_hash(&loaded.files, &crate_name, &module_name, options)?;
+    if let Some(previous) = &previous {
+        let complete = previous
+            .files
+            .iter()
+            .all(|file| output_dir.join(file).exists());
+        if previous.hash.as_deref() == Some(hash.as_str()) && complete {
+            return Ok(output_dir);
+        }
+    }
+
+    let schema = crate::passes::run_frontend(&loaded.ast)?;
+    let lowered = lower_queries(&schema)?;
+    let files = if options.module_only {
+        let file_name = module_name
+            .unwrap_or_else(|| sanitize_ident(&input_base_name(input)));
+        vec![(
+            format!("{}.rs", file_name),
+            render_lib_rs(&schema, &lowered, options.async_api)?,
+        )]
+    } else {
+        render_crate_files(
+            &schema,
+            &lowered,
+            &crate_name,
+            module_name.as_deref(),
+            options,
+        )?
+    };
+
+    // A new directory is marked before anything else is written, so that
+    // an interrupted build can still be retried.
+    if previous.is_none() {
+        fs::create_dir_all(&output_dir)?;
+        fs::write(output_dir.join(MARKER_FILE), render_manifest(None, &[]))?;
+    }
+    for (path, contents) in &files {
+        write_if_changed(&output_dir.join(path), contents)?;
+    }
+    if let Some(previous) = &previous {
+        for stale in &previous.files {
+            let still_generated = files.iter().any(|(path, _)| path == stale);
+            let stale = output_dir.join(stale);
+            if !still_generated && stale.is_file() {
+                fs::remove_file(stale)?;
+            }
+        }
+    }
+    let paths = files.into_iter().map(|(path, _)| path).collect::<Vec<_>>();
+    write_if_changed(
+        &output_dir.join(MARKER_FILE),
+        &render_manifest(Some(&hash), &paths),
     )?;
 
     Ok(output_dir)
 }
 
+fn render_crate_files(
+    schema: &ResolvedSchema,
+    lowered: &[LoweredQuery],
+    crate_name: &str,
+    module_name: Option<&str>,
+    options: &BuildOptions,
+) -> Result<Vec<(String, String)>, Error> {
+    let mut files = vec![(
+        "Cargo.toml".to_string(),
+        render_cargo_toml(crate_name, &options.rusqlite, options.async_api),
+    )];
+    let module = render_lib_rs(schema, lowered, options.async_api)?;
+    let db_path = match module_name {
+        Some(module_name) => {
+            files.push((format!("src/{}.rs", module_name), module));
+            files.push((
+                "src/lib.rs".to_string(),
+                format!("pub mod {};\n", module_name),
+            ));
+            format!("{}::{}", crate_name.replace('-', "_"), module_name)
+        }
+        None => {
+            files.push(("src/lib.rs".to_string(), module));
+            crate_name.replace('-', "_")
+        }
+    };
+    if options.demo {
+        files.push((
+            "src/main.rs".to_string(),
+            render_main_rs(schema, lowered, &db_path)?,
+        ));
+    }
+    Ok(files)
+}
+
+// The marker file doubles as the build manifest: the hash of the inputs the
+// directory was generated from, and the files generated.
+struct Manifest {
+    hash: Option<String>,
+    files: Vec<String>,
+}
+
+// Returns the manifest of an existing output directory, refusing to touch
+// a non-empty directory schemaforge did not generate, so that a mistyped
+// `--out-dir` cannot clobber a source tree.
+fn read_manifest(output_dir: &Path) -> Result<Option<Manifest>, Error> {
+    if !output_dir.exists() {
+        return Ok(None);
+    }
+    if !output_dir.is_dir() {
+        return Err(Error::Pass(format!(
+            "output path '{}' exists and is not a directory",
+            output_dir.display()
+        )));
+    }
+
+    let marker = output_dir.join(MARKER_FILE);
+    if !marker.exists() {
+        if fs::read_dir(output_dir)?.next().is_none() {
+            return Ok(None);
+        }
+        return Err(Error::Pass(format!(
+            "refusing to overwrite '{}': it has no {} marker, so it was not generated by schemaforge",
+            output_dir.display(),
+            MARKER_FILE
+        )));
+    }
+
+    let mut manifest = Manifest {
+        hash: None,
+        files: Vec::new(),
+    };
+    for line in fs::read_to_string(marker)?.lines() {
+        if let Some(hash) = line.strip_prefix("hash ") {
+            manifest.hash = Some(hash.to_string());
+        } else if let Some(file) = line.strip_prefix("file ") {
+            manifest.files.push(file.to_string());
+        }
+    }
+    Ok(Some(manifest))
+}
+
+fn render_manifest(hash: Option<&str>, files: &[String]) -> String {
+    let mut out = String::from(
+        "# Generated by schemaforge. `schemaforge build` rewrites the files\n# listed here when the schema changes; do not edit them.\n",
+    );
+    if let Some(hash) = hash {
+        out.push_str(&format!("hash {}\n", hash));
+    }
+    for file in files {
+        out.push_str(&format!("file {}\n", file));
+    }
+    out
+}
+
+// FNV-1a over the generator version, the build options and the contents
+// of every schema file in load order. Generator changes only invalidate
+// existing output when the crate version is bumped.
+fn input_hash(
+    files: &[PathBuf],
+    crate_name: &str,
+    module_name: &Option<String>,
+    options: &BuildOptions,
+) -> Result<String, Error> {
+    let mut hash = 0xcbf2_9ce4_8422_2325_u64;
+    let mut feed = |bytes: &[u8]| {
+        for byte in bytes {
+            hash ^= u64::from(*byte);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    };
+
+    feed(
+        format!(
+            "schemaforge {}\ncrate {}\nmodule {:?}\ndemo {}\nrusqlite {:?}\nmodule-only {}\nasync {}\n",
+            env!("CARGO_PKG_VERSION"),
+            crate_name,
+            module_name,
+            options.demo,
+            options.rusqlite,
+            options.module_only,
+            options.async_api
+        )
+        .as_bytes(),
+    );
+    for file in files {
+        let contents = fs::read(file)?;
+        feed(format!("file {}\n", contents.len()).as_bytes());
+        feed(&contents);
+    }
+    Ok(format!("{:016x}", hash))
+}
+
+// Leaves unchanged files untouched so that their modification times, and
+// cargo's fingerprints of dependents, survive a rebuild.
+fn write_if_changed(path: &Path, contents: &str) -> Result<(), Error> {
+    if fs::read(path).ok().as_deref() == Some(contents.as_bytes()) {
+        return Ok(());
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, contents)?;
+    Ok(())
+}
+
+fn validate_crate_name(name: &str) -> Result<String, Error> {
+    let valid = name
+        .chars()
+        .next()
+        .map(|ch| ch.is_ascii_alphabetic())
+        .unwrap_or(false)
+        && name
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '-' || ch == '_');
+    if !valid {
+        return Err(Error::Pass(format!(
+            "invalid crate name '{}': use ASCII letters, digits, '-' and '_', starting with a letter",
+            name
+        )));
+    }
+    Ok(name.to_string())
+}
+
+fn validate_module_name(name: &str) -> Result<String, Error> {
+    if sanitize_ident(name) != name || matches!(name, "lib" | "main") {
+        return Err(Error::Pass(format!(
+            "invalid module name '{}': use a lowercase Rust identifier other than 'lib' or 'main'",
+            name
+        )));
+    }
+    Ok(name.to_string())
+}
+
+// Versions and features are written into `Cargo.toml` as quoted strings.
+fn validate_rusqlite(rusqlite: &RusqliteDependency) -> Result<(), Error> {
+    let (version, features) = match rusqlite {
+        RusqliteDependency::Version { version, features } => {
+            (Some(version), features)
+        }
+        RusqliteDependency::Workspace { features } => (None, features),
+    };
+    for value in version.into_iter().chain(features) {
+        let valid = !value.is_empty()
+            && value.chars().all(|ch| {
+                ch.is_ascii_alphanumeric() || "-_.+/=<>^~*, ".contains(ch)
+            });
+        if !valid {
+            return Err(Error::Pass(format!(
+                "invalid rusqlite version or feature '{}'",
+                value
+            )));
+        }
+    }
+    Ok(())
+}
+
+// Renders the schema as a single Rust module for a consumer's `build.rs`,
+// to be pulled in with
+// `include!(concat!(env!("OUT_DIR"), "/<out_file>"))`. The module needs the
+// same `rusqlite` dependency as the crate `build` generates.
+// Prints `cargo:rerun-if-changed` for the schema and every file it includes.
+pub fn compile_to_module(
+    schema_path: &Path,
+    out_file: &Path,
+) -> Result<(), Error> {
+    // The root is announced first so that cargo retries once a schema that
+    // fails to load is fixed.
+    println!("cargo:rerun-if-changed={}", schema_path.display());
+    let loaded = load_schema(schema_path)?;
+    for file in loaded.files.iter().skip(1) {
+        println!("cargo:rerun-if-changed={}", file.display());
+    }
+
+    let schema = crate::passes::run_frontend(&loaded.ast)?;
+    let module = render_module(&schema)?;
+
+    if let Some(parent) = out_file.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(out_file, module)?;
+    Ok(())
+}
+
+// Renders the `Db` module for a resolved schema, as written to the generated
+// crate's `src/lib.rs`.
+pub fn render_module(schema: &ResolvedSchema) -> Result<String, Error> {
+    render_module_with(schema, false)
+}
+
+// Like `render_module`, optionally adding `AsyncDb`.
+pub fn render_module_with(
+    schema: &ResolvedSchema,
+    async_api: bool,
+) -> Result<String, Error> {
+    let lowered = lower_queries(schema)?;
+    render_lib_rs(schema, &lowered, async_api)
+}
+
 fn output_dir(input: &Path) -> PathBuf {
     let dir = sanitize_component(&input_base_name(input));
     PathBuf::from("target").join("schemaforge-out").join(dir)
@@ -52,20 +409,74 @@ fn crate_name(input: &Path) -> String {
     )
 }
 
-fn render_cargo_toml(crate_name: &str) -> String {
+// A crate inheriting workspace dependencies must be a workspace member, so
+// only a crate with its own rusqlite version is made a workspace root.
+fn render_cargo_toml(
+    crate_name: &str,
+    rusqlite: &RusqliteDependency,
+    async_api: bool,
+) -> String {
+    let (dependency, workspace) = match rusqlite {
+        RusqliteDependency::Version { version, features }
+            if features.is_empty() =>
+        {
+            (format!("\"{}\"", version), "\n[workspace]\n")
+        }
+        RusqliteDependency::Version { version, features } => (
+            format!(
+                "{{ version = \"{}\", features = [{}] }}",
+                version,
+                toml_string_list(features)
+            ),
+            "\n[workspace]\n",
+        ),
+        RusqliteDependency::Workspace { features } if features.is_empty() => {
+            ("{ workspace = true }".to_string(), "")
+        }
+        RusqliteDependency::Workspace { features } => (
+            format!(
+                "{{ workspace = true, features = [{}] }}",
+                toml_string_list(features)
+            ),
+            "",
+        ),
+    };
+    let futures_channel = if async_api {
+        "futures-channel = \"0.3\"\n"
+    } else {
+        ""
+    };
     format!(
-        "[package]\nname = \"{}\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[dependencies]\nanyhow = \"1\"\nrusqlite = \"0.31\"\n\n[workspace]\n",
-        crate_name
+        "[package]\nname = \"{}\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[dependencies]\n{}rusqlite = {}\n{}",
+        crate_name, futures_channel, dependency, workspace
     )
 }
 
+fn toml_string_list(values: &[String]) -> String {
+    values
+        .iter()
+        .map(|value| format!("\"{}\"", value))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 fn render_lib_rs(
     schema: &ResolvedSchema,
     lowered: &[LoweredQuery],
+    async_api: bool,
 ) -> Result<String, Error> {
     let mut create_table_sql = Vec::new();
     for table in &schema.tables {
-        create_table_sql.push(format!("{};", compile_create_table_sql(table)?));
+        create_table_sql
+            .push(format!("{};", compile_create_table_sql(table, schema)?));
+    }
+
+    check_type_names(schema)?;
+
+    let mut type_defs = String::new();
+    for enum_def in &schema.enums {
+        type_defs.push_str(&render_enum(enum_def)?);
+        type_defs.push('\n');
     }
 
     let lowered_map = lowered
@@ -74,9 +485,14 @@ fn render_lib_rs(
         .collect::<HashMap<_, _>>();
 
     let mut proc_methods = String::new();
+    let mut async_methods = String::new();
     for proc_def in &schema.procs {
+        type_defs.push_str(&render_proc_row(proc_def, schema)?);
+        type_defs.push('\n');
         proc_methods.push_str(&render_proc_method(proc_def, schema)?);
         proc_methods.push('\n');
+        async_methods.push_str(&render_async_proc_methods(proc_def, schema)?);
+        async_methods.push('\n');
     }
 
     let mut query_methods = String::new();
@@ -93,17 +509,429 @@ fn render_lib_rs(
             schema,
         )?);
         query_methods.push('\n');
+        query_methods.push_str(&render_explain_method(
+            query,
+            &lowered_query.plan,
+            schema,
+        )?);
+        query_methods.push('\n');
+        async_methods.push_str(&render_async_query_method(
+            query,
+            &lowered_query.plan,
+            schema,
+        )?);
+        async_methods.push('\n');
     }
 
+    // `params!` and `constraint_error` are only used by proc methods.
+    let (imports, constraint_error_fn) = if schema.procs.is_empty() {
+        ("use rusqlite::Connection;", "")
+    } else {
+        ("use rusqlite::{params, Connection};", CONSTRAINT_ERROR_FN)
+    };
+
+    // A schema with only tables generates no methods reading the connection
+    // or calling into the `AsyncDb` thread.
+    let conn_attr = if schema.procs.is_empty() && schema.queries.is_empty() {
+        "    #[allow(dead_code)]\n"
+    } else {
+        ""
+    };
+    let async_db = if async_api {
+        format!(
+            "\n{}{}{}{}}}\n",
+            ASYNC_DB_TYPES, conn_attr, ASYNC_CALL_FN, async_methods
+        )
+    } else {
+        String::new()
+    };
+
+    // Proc and query methods reuse their statements through rusqlite's
+    // statement cache, which holds 16 statements unless told otherwise.
+    let statements = schema.procs.len() + schema.queries.len();
+    let cache_capacity = if statements > 16 {
+        format!(
+            "        conn.set_prepared_statement_cache_capacity({});\n",
+            statements
+        )
+    } else {
+        String::new()
+    };
+
     let create_batch = create_table_sql.join("\n");
     let create_batch_literal = rust_string_literal(&create_batch);
 
     Ok(format!(
-        "use rusqlite::{{params, Connection}};\n\npub struct Db {{\n    conn: Connection,\n}}\n\nimpl Db {{\n    pub fn new() -> anyhow::Result<Self> {{\n        let conn = Connection::open_in_memory()?;\n        conn.execute_batch({})?;\n        Ok(Self {{ conn }})\n    }}\n\n{}{}\n}}\n",
-        create_batch_literal, proc_methods, query_methods
+        "{}\n\n{}{}{}pub struct Db {{\n{}    conn: Connection,\n}}\n\nimpl Db {{\n    pub fn new() -> Result<Self, Error> {{\n        let conn = Connection::open_in_memory()?;\n{}        conn.execute_batch({})?;\n        Ok(Self {{ conn }})\n    }}\n\n{}{}\n}}\n{}",
+        imports,
+        error_types(async_api),
+        constraint_error_fn,
+        type_defs,
+        conn_attr,
+        cache_capacity,
+        create_batch_literal,
+        proc_methods,
+        query_methods,
+        async_db
+    ))
+}
+
+// `Closed` is only reachable through `AsyncDb`, so sync crates leave it out
+// and callers can match on `Error` without a dead arm.
+fn error_types(async_api: bool) -> String {
+    let closed = |piece: &'static str| if async_api { piece } else { "" };
+    [
+        ERROR_ENUM,
+        closed(CLOSED_VARIANT),
+        ERROR_DISPLAY,
+        closed(CLOSED_DISPLAY_ARM),
+        ERROR_SOURCE,
+        closed(CLOSED_SOURCE_ARM),
+        ERROR_CONVERSION,
+    ]
+    .concat()
+}
+
+const ERROR_ENUM: &str = r#"#[derive(Debug)]
+pub enum Error {
+    /// SQLite failed for a reason not covered by another variant.
+    Sqlite(rusqlite::Error),
+    /// An insert violated a constraint of `table`. `check` names the CHECK
+    /// constraint that failed, if it was one.
+    Constraint {
+        table: &'static str,
+        check: Option<String>,
+        message: String,
+    },
+    /// A row was expected but none matched.
+    NotFound,
+    /// A column value could not be converted to its Rust type.
+    Decode {
+        column: usize,
+        source: rusqlite::Error,
+    },
+"#;
+
+const CLOSED_VARIANT: &str = r#"    /// The thread running an `AsyncDb` has stopped.
+    Closed,
+"#;
+
+const ERROR_DISPLAY: &str = r#"}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Sqlite(err) => write!(f, "sqlite error: {}", err),
+            Error::Constraint {
+                table,
+                check: Some(check),
+                ..
+            } => write!(f, "check '{}' failed on table '{}'", check, table),
+            Error::Constraint { table, message, .. } => {
+                write!(f, "constraint failed on table '{}': {}", table, message)
+            }
+            Error::NotFound => write!(f, "no matching row"),
+            Error::Decode { column, source } => {
+                write!(f, "cannot decode column {}: {}", column, source)
+            }
+"#;
+
+const CLOSED_DISPLAY_ARM: &str = r#"            Error::Closed => write!(f, "database thread has stopped"),
+"#;
+
+const ERROR_SOURCE: &str = r#"        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Sqlite(err) | Error::Decode { source: err, .. } => Some(err),
+            Error::Constraint { .. } | Error::NotFound => None,
+"#;
+
+const CLOSED_SOURCE_ARM: &str = r#"            Error::Closed => None,
+"#;
+
+const ERROR_CONVERSION: &str = r#"        }
+    }
+}
+
+impl From<rusqlite::Error> for Error {
+    fn from(err: rusqlite::Error) -> Self {
+        match err {
+            rusqlite::Error::QueryReturnedNoRows => Error::NotFound,
+            rusqlite::Error::FromSqlConversionFailure(column, ..)
+            | rusqlite::Error::InvalidColumnType(column, ..)
+            | rusqlite::Error::IntegralValueOutOfRange(column, _) => {
+                Error::Decode {
+                    column,
+                    source: err,
+                }
+            }
+            err => Error::Sqlite(err),
+        }
+    }
+}
+
+"#;
+
+// `AsyncDb` owns a `Db` on a thread of its own and sends it each call as a
+// boxed closure, replying through a oneshot channel that any executor can
+// await. `pascal_case` never starts a name with `__`, so the job alias
+// cannot collide with a schema type.
+const ASYNC_DB_TYPES: &str = r#"type __SchemaforgeJob = Box<dyn FnOnce(&mut Db) + Send>;
+
+pub struct AsyncDb {
+    jobs: std::sync::mpsc::Sender<__SchemaforgeJob>,
+}
+
+impl AsyncDb {
+    pub async fn new() -> Result<Self, Error> {
+        let (jobs, queue) = std::sync::mpsc::channel::<__SchemaforgeJob>();
+        let (opened, reply) = futures_channel::oneshot::channel();
+        std::thread::spawn(move || match Db::new() {
+            Ok(mut db) => {
+                let _ = opened.send(Ok(()));
+                for job in queue {
+                    job(&mut db);
+                }
+            }
+            Err(err) => {
+                let _ = opened.send(Err(err));
+            }
+        });
+        reply.await.map_err(|_| Error::Closed)??;
+        Ok(AsyncDb { jobs })
+    }
+
+"#;
+
+const ASYNC_CALL_FN: &str = r#"    async fn call<T: Send + 'static>(
+        &self,
+        call: impl FnOnce(&mut Db) -> Result<T, Error> + Send + 'static,
+    ) -> Result<T, Error> {
+        let (sender, reply) = futures_channel::oneshot::channel();
+        self.jobs
+            .send(Box::new(move |db| {
+                let _ = sender.send(call(db));
+            }))
+            .map_err(|_| Error::Closed)?;
+        reply.await.map_err(|_| Error::Closed)?
+    }
+
+"#;
+
+const CONSTRAINT_ERROR_FN: &str = r#"fn constraint_error(table: &'static str, err: rusqlite::Error) -> Error {
+    match &err {
+        rusqlite::Error::SqliteFailure(failure, Some(message))
+            if failure.code == rusqlite::ErrorCode::ConstraintViolation =>
+        {
+            Error::Constraint {
+                table,
+                check: message
+                    .strip_prefix("CHECK constraint failed: ")
+                    .map(str::to_string),
+                message: message.clone(),
+            }
+        }
+        _ => err.into(),
+    }
+}
+
+"#;
+
+fn param_has_default(
+    param: &crate::ir::schema::ProcParamIr,
+    schema: &ResolvedSchema,
+) -> bool {
+    schema
+        .column(param.column)
+        .map(|column| column.default.is_some())
+        .unwrap_or(false)
+}
+
+// Types the generated module declares whatever the schema. `AsyncDb` is
+// reserved even when the async API is off, so that turning it on never
+// breaks a schema.
+const GENERATED_TYPES: [&str; 3] = ["Db", "AsyncDb", "Error"];
+
+// Rejects enums whose Rust type name would collide with another generated
+// type.
+fn check_type_names(schema: &ResolvedSchema) -> Result<(), Error> {
+    for (index, enum_def) in schema.enums.iter().enumerate() {
+        let type_name = pascal_case(&enum_def.name);
+        if GENERATED_TYPES.contains(&type_name.as_str()) {
+            return Err(Error::Pass(format!(
+                "enum '{}' conflicts with the generated {} type",
+                enum_def.name, type_name
+            )));
+        }
+        if let Some(other) = schema.enums[..index]
+            .iter()
+            .find(|other| pascal_case(&other.name) == type_name)
+        {
+            return Err(Error::Pass(format!(
+                "enums '{}' and '{}' both map to Rust type '{}'",
+                other.name, enum_def.name, type_name
+            )));
+        }
+        if let Some(proc_def) = schema
+            .procs
+            .iter()
+            .find(|proc_def| proc_row_type(proc_def) == type_name)
+        {
+            return Err(Error::Pass(format!(
+                "enum '{}' conflicts with the row type '{}' of proc '{}'",
+                enum_def.name, type_name, proc_def.name
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn render_enum(enum_def: &EnumIr) -> Result<String, Error> {
+    let type_name = pascal_case(&enum_def.name);
+    let mut variant_names: Vec<String> = Vec::new();
+    for variant in &enum_def.variants {
+        let variant_name = pascal_case(variant);
+        if let Some(index) =
+            variant_names.iter().position(|name| *name == variant_name)
+        {
+            return Err(Error::Pass(format!(
+                "enum '{}' variants '{}' and '{}' both map to Rust variant '{}'",
+                enum_def.name, enum_def.variants[index], variant, variant_name
+            )));
+        }
+        variant_names.push(variant_name);
+    }
+
+    let mut declarations = String::new();
+    let mut as_str_arms = String::new();
+    let mut to_sql_arms = String::new();
+    let mut from_sql_arms = String::new();
+    for (index, (variant, variant_name)) in
+        enum_def.variants.iter().zip(&variant_names).enumerate()
+    {
+        let literal = rust_string_literal(variant);
+        declarations.push_str(&format!("    {},\n", variant_name));
+        as_str_arms.push_str(&format!(
+            "            {}::{} => {},\n",
+            type_name, variant_name, literal
+        ));
+        match enum_def.repr.as_str() {
+            "i64" => {
+                to_sql_arms.push_str(&format!(
+                    "            {}::{} => {},\n",
+                    type_name, variant_name, index
+                ));
+                from_sql_arms.push_str(&format!(
+                    "            {} => Ok({}::{}),\n",
+                    index, type_name, variant_name
+                ));
+            }
+            _ => from_sql_arms.push_str(&format!(
+                "            {} => Ok({}::{}),\n",
+                literal, type_name, variant_name
+            )),
+        }
+    }
+
+    let (to_sql_body, from_sql_body) = match enum_def.repr.as_str() {
+        "i64" => (
+            format!(
+                "        let value: i64 = match self {{\n{}        }};\n        Ok(rusqlite::types::ToSqlOutput::from(value))\n",
+                to_sql_arms
+            ),
+            format!(
+                "        match value.as_i64()? {{\n{}            other => Err(rusqlite::types::FromSqlError::OutOfRange(other)),\n        }}\n",
+                from_sql_arms
+            ),
+        ),
+        "text" => (
+            "        Ok(rusqlite::types::ToSqlOutput::from(self.as_str()))\n"
+                .to_string(),
+            format!(
+                "        match value.as_str()? {{\n{}            other => Err(rusqlite::types::FromSqlError::Other(\n                format!(\"unknown {} variant '{{}}'\", other).into(),\n            )),\n        }}\n",
+                from_sql_arms,
+                escape_rust_string(&enum_def.name)
+            ),
+        ),
+        other => {
+            return Err(Error::Pass(format!(
+                "unsupported enum repr '{}' for generated Rust code",
+                other
+            )))
+        }
+    };
+
+    Ok(format!(
+        "#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]\npub enum {0} {{\n{1}}}\n\nimpl {0} {{\n    pub fn as_str(&self) -> &'static str {{\n        match self {{\n{2}        }}\n    }}\n}}\n\nimpl rusqlite::types::ToSql for {0} {{\n    fn to_sql(&self) -> rusqlite::Result<rusqlite::types::ToSqlOutput<'_>> {{\n{3}    }}\n}}\n\nimpl rusqlite::types::FromSql for {0} {{\n    fn column_result(\n        value: rusqlite::types::ValueRef<'_>,\n    ) -> rusqlite::types::FromSqlResult<Self> {{\n{4}    }}\n}}\n",
+        type_name, declarations, as_str_arms, to_sql_body, from_sql_body
     ))
 }
 
+// Argument names and Rust types of a proc's parameters, in order. Params
+// naming a defaulted column are `Option`s, `None` meaning "use the default";
+// see `compile_insert_proc_sql`.
+fn proc_args(
+    proc_def: &ProcIr,
+    schema: &ResolvedSchema,
+) -> Result<Vec<(String, String)>, Error> {
+    let mut args = Vec::new();
+    for param in &proc_def.params {
+        let mut arg_ty = rust_type_name(&param.ty, schema)?;
+        if param_has_default(param, schema) {
+            arg_ty = format!("Option<{}>", arg_ty);
+        }
+        args.push((sanitize_ident(&param.name), arg_ty));
+    }
+    Ok(args)
+}
+
+fn proc_table<'a>(
+    proc_def: &ProcIr,
+    schema: &'a ResolvedSchema,
+) -> Result<&'a TableIr, Error> {
+    schema.tables.get(proc_def.table).ok_or_else(|| {
+        Error::Pass(format!(
+            "proc '{}' references unknown table id {}",
+            proc_def.name, proc_def.table
+        ))
+    })
+}
+
+// Procs on tables with an auto-increment key return the new row's id.
+fn proc_returns_rowid(
+    proc_def: &ProcIr,
+    schema: &ResolvedSchema,
+) -> Result<bool, Error> {
+    let table = proc_table(proc_def, schema)?;
+    Ok(table.fields.iter().any(|field| field.auto_increment))
+}
+
+fn proc_row_type(proc_def: &ProcIr) -> String {
+    format!("{}Row", pascal_case(&proc_def.name))
+}
+
+// One row for `<proc>_many`, with a field per proc parameter.
+fn render_proc_row(
+    proc_def: &ProcIr,
+    schema: &ResolvedSchema,
+) -> Result<String, Error> {
+    let fields = proc_args(proc_def, schema)?
+        .into_iter()
+        .map(|(name, ty)| format!("    pub {}: {},\n", name, ty))
+        .collect::<String>();
+    Ok(format!(
+        "#[derive(Clone, Debug, PartialEq)]\npub struct {} {{\n{}}}\n",
+        proc_row_type(proc_def),
+        fields
+    ))
+}
+
+// Each proc inserts one row, and `<proc>_many` inserts a batch in a single
+// transaction, rolling back every row of the batch if one fails.
 fn render_proc_method(
     proc_def: &ProcIr,
     schema: &ResolvedSchema,
@@ -112,21 +940,93 @@ fn render_proc_method(
     let insert_sql = compile_insert_proc_sql(proc_def, schema)?;
     let insert_sql_literal = rust_string_literal(&insert_sql);
 
-    let mut signature_params = Vec::new();
-    let mut arg_names = Vec::new();
-    for param in &proc_def.params {
-        let arg_name = sanitize_ident(&param.name);
-        let arg_ty = rust_type_name(&param.ty)?;
-        signature_params.push(format!("{}: {}", arg_name, arg_ty));
-        arg_names.push(arg_name);
-    }
+    let args = proc_args(proc_def, schema)?;
+    let signature_params = args
+        .iter()
+        .map(|(name, ty)| format!("{}: {}", name, ty))
+        .collect::<Vec<_>>();
+    let arg_names = args
+        .iter()
+        .map(|(name, _)| name.clone())
+        .collect::<Vec<_>>();
+    let row_args = args
+        .iter()
+        .map(|(name, _)| format!("row.{}", name))
+        .collect::<Vec<_>>();
+
+    let table = proc_table(proc_def, schema)?;
+    let table_literal = rust_string_literal(&table.name);
+
+    let (return_ty, return_value) = if proc_returns_rowid(proc_def, schema)? {
+        ("i64", "self.conn.last_insert_rowid()")
+    } else {
+        ("()", "()")
+    };
 
     Ok(format!(
-        "    pub fn {}(&mut self, {}) -> anyhow::Result<()> {{\n        self.conn.execute({}, params![{}])?;\n        Ok(())\n    }}\n",
+        "    pub fn {}(&mut self, {}) -> Result<{}, Error> {{\n        self.conn\n            .prepare_cached({})?\n            .execute(params![{}])\n            .map_err(|err| constraint_error({}, err))?;\n        Ok({})\n    }}\n\n    pub fn {}_many(&mut self, rows: impl IntoIterator<Item = {}>) -> Result<usize, Error> {{\n        let tx = self.conn.transaction()?;\n        let mut count = 0;\n        {{\n            let mut stmt = tx.prepare_cached({})?;\n            for row in rows {{\n                stmt.execute(params![{}])\n                    .map_err(|err| constraint_error({}, err))?;\n                count += 1;\n            }}\n        }}\n        tx.commit()?;\n        Ok(count)\n    }}\n",
         method_name,
         signature_params.join(", "),
+        return_ty,
         insert_sql_literal,
-        arg_names.join(", ")
+        arg_names.join(", "),
+        table_literal,
+        return_value,
+        method_name,
+        proc_row_type(proc_def),
+        insert_sql_literal,
+        row_args.join(", "),
+        table_literal
+    ))
+}
+
+// Forwards a proc and its `_many` form to the `AsyncDb` thread.
+fn render_async_proc_methods(
+    proc_def: &ProcIr,
+    schema: &ResolvedSchema,
+) -> Result<String, Error> {
+    let method_name = sanitize_ident(&proc_def.name);
+    let args = proc_args(proc_def, schema)?;
+    let signature_params = args
+        .iter()
+        .map(|(name, ty)| format!("{}: {}", name, ty))
+        .collect::<Vec<_>>();
+    let arg_names = args
+        .iter()
+        .map(|(name, _)| name.clone())
+        .collect::<Vec<_>>();
+    let return_ty = if proc_returns_rowid(proc_def, schema)? {
+        "i64"
+    } else {
+        "()"
+    };
+
+    Ok(format!(
+        "    pub async fn {}(&self, {}) -> Result<{}, Error> {{\n        self.call(move |db| db.{}({})).await\n    }}\n\n    pub async fn {}_many(&self, rows: impl IntoIterator<Item = {}> + Send + 'static) -> Result<usize, Error> {{\n        self.call(move |db| db.{}_many(rows)).await\n    }}\n",
+        method_name,
+        signature_params.join(", "),
+        return_ty,
+        method_name,
+        arg_names.join(", "),
+        method_name,
+        proc_row_type(proc_def),
+        method_name
+    ))
+}
+
+fn render_async_query_method(
+    query: &QueryIr,
+    plan: &crate::plan::Plan,
+    schema: &ResolvedSchema,
+) -> Result<String, Error> {
+    let compiled = compile_plan_to_sql(plan, schema)?;
+    let method_name = sanitize_ident(&query.name);
+    let tuple_type =
+        result_tuple_type(&query.name, &compiled.result_columns, schema)?;
+
+    Ok(format!(
+        "    pub async fn {}(&self) -> Result<Vec<{}>, Error> {{\n        self.call(|db| db.{}()).await\n    }}\n",
+        method_name, tuple_type, method_name
     ))
 }
 
@@ -139,26 +1039,32 @@ fn render_query_method(
     let method_name = sanitize_ident(&query.name);
     let sql_literal = rust_string_literal(&compiled.sql);
 
-    let mut rust_types = Vec::new();
-    for column_id in &compiled.result_columns {
-        let field = schema.column(*column_id).ok_or_else(|| {
-            Error::Pass(format!(
-                "query '{}' references unknown column id {}:{}",
-                query.name, column_id.table, column_id.column
-            ))
-        })?;
-        rust_types.push(rust_type_name(&field.ty)?.to_string());
-    }
-
-    let tuple_type = tuple_type(&rust_types);
+    let tuple_type =
+        result_tuple_type(&query.name, &compiled.result_columns, schema)?;
     let tuple_decode = tuple_decode_expr(&compiled.result_columns, schema)?;
 
     Ok(format!(
-        "    pub fn {}(&self) -> anyhow::Result<Vec<{}>> {{\n        let mut stmt = self.conn.prepare({})?;\n        let rows = stmt.query_map([], |row| {{\n            Ok({})\n        }})?;\n\n        let mut out = Vec::new();\n        for row in rows {{\n            out.push(row?);\n        }}\n        Ok(out)\n    }}\n",
+        "    pub fn {}(&self) -> Result<Vec<{}>, Error> {{\n        let mut stmt = self.conn.prepare_cached({})?;\n        let rows = stmt.query_map([], |row| {{\n            Ok({})\n        }})?;\n\n        let mut out = Vec::new();\n        for row in rows {{\n            out.push(row?);\n        }}\n        Ok(out)\n    }}\n",
         method_name, tuple_type, sql_literal, tuple_decode
     ))
 }
 
+fn render_explain_method(
+    query: &QueryIr,
+    plan: &crate::plan::Plan,
+    schema: &ResolvedSchema,
+) -> Result<String, Error> {
+    let compiled = compile_plan_to_sql(plan, schema)?;
+    let method_name = format!("explain_{}", sanitize_ident(&query.name));
+    let sql_literal =
+        rust_string_literal(&format!("EXPLAIN QUERY PLAN {}", compiled.sql));
+
+    Ok(format!(
+        "    #[cfg(debug_assertions)]\n    pub fn {}(&self) -> Result<Vec<String>, Error> {{\n        let mut stmt = self.conn.prepare({})?;\n        let rows = stmt.query_map([], |row| row.get::<_, String>(3))?;\n\n        let mut out = Vec::new();\n        for row in rows {{\n            out.push(row?);\n        }}\n        Ok(out)\n    }}\n",
+        method_name, sql_literal
+    ))
+}
+
 fn tuple_decode_expr(
     columns: &[ColumnId],
     schema: &ResolvedSchema,
@@ -174,7 +1080,7 @@ fn tuple_decode_expr(
 
         values.push(format!(
             "row.get::<_, {}>({})?",
-            rust_type_name(&field.ty)?,
+            rust_type_name(&field.ty, schema)?,
             index
         ));
     }
@@ -186,6 +1092,25 @@ fn tuple_decode_expr(
     }
 }
 
+// The Rust tuple type a generated query method returns one of per row.
+pub(crate) fn result_tuple_type(
+    query_name: &str,
+    columns: &[ColumnId],
+    schema: &ResolvedSchema,
+) -> Result<String, Error> {
+    let mut rust_types = Vec::new();
+    for column_id in columns {
+        let field = schema.column(*column_id).ok_or_else(|| {
+            Error::Pass(format!(
+                "query '{}' references unknown column id {}:{}",
+                query_name, column_id.table, column_id.column
+            ))
+        })?;
+        rust_types.push(rust_type_name(&field.ty, schema)?);
+    }
+    Ok(tuple_type(&rust_types))
+}
+
 fn tuple_type(types: &[String]) -> String {
     if types.len() == 1 {
         format!("({},)", types[0])
@@ -194,10 +1119,17 @@ fn tuple_type(types: &[String]) -> String {
     }
 }
 
-fn rust_type_name(type_name: &str) -> Result<&'static str, Error> {
+fn rust_type_name(
+    type_name: &str,
+    schema: &ResolvedSchema,
+) -> Result<String, Error> {
+    if schema.enum_type(type_name).is_some() {
+        return Ok(pascal_case(type_name));
+    }
+
     match type_name {
-        "i64" => Ok("i64"),
-        "text" => Ok("String"),
+        "i64" => Ok("i64".to_string()),
+        "text" => Ok("String".to_string()),
         other => Err(Error::Pass(format!(
             "unsupported scalar type '{}' for generated Rust code",
             other
@@ -205,51 +1137,58 @@ fn rust_type_name(type_name: &str) -> Result<&'static str, Error> {
     }
 }
 
+// The demo inserts two rows through the first proc and prints the rows of
+// the first query, skipping whichever the schema does not declare.
 fn render_main_rs(
     schema: &ResolvedSchema,
     lowered: &[LoweredQuery],
-    crate_name: &str,
+    db_path: &str,
 ) -> Result<String, Error> {
-    let proc_def = schema.procs.first().ok_or_else(|| {
-        Error::Pass("build requires at least one proc".into())
-    })?;
-    let query_def = schema.queries.first().ok_or_else(|| {
-        Error::Pass("build requires at least one query".into())
-    })?;
-
-    let proc_name = sanitize_ident(&proc_def.name);
-    let query_name = sanitize_ident(&query_def.name);
-
-    let mut demo_calls = String::new();
-    for row_index in 0..2 {
-        let mut args = Vec::new();
-        for (param_index, param) in proc_def.params.iter().enumerate() {
-            args.push(demo_value(param, row_index, param_index)?);
+    let mut body = String::new();
+
+    if let Some(proc_def) = schema.procs.first() {
+        let proc_name = sanitize_ident(&proc_def.name);
+        for row_index in 0..2 {
+            let mut args = Vec::new();
+            for (param_index, param) in proc_def.params.iter().enumerate() {
+                args.push(demo_value(
+                    param,
+                    row_index,
+                    param_index,
+                    schema,
+                    db_path,
+                )?);
+            }
+            body.push_str(&format!(
+                "    db.{}({})?;\n",
+                proc_name,
+                args.join(", ")
+            ));
         }
-        demo_calls.push_str(&format!(
-            "    db.{}({})?;\n",
-            proc_name,
-            args.join(", ")
-        ));
+        body.push('\n');
     }
 
-    // Ensure lowering happened for at least the first query in this demo build.
-    let lowered_map = lowered
-        .iter()
-        .map(|query| query.name.as_str())
-        .collect::<Vec<_>>();
-    if !lowered_map.iter().any(|name| *name == query_def.name) {
-        return Err(Error::Pass(format!(
-            "missing lowered plan for query '{}'",
-            query_def.name
-        )));
+    if let Some(query_def) = schema.queries.first() {
+        if !lowered.iter().any(|query| query.name == query_def.name) {
+            return Err(Error::Pass(format!(
+                "missing lowered plan for query '{}'",
+                query_def.name
+            )));
+        }
+        body.push_str(&format!(
+            "    let rows = db.{}()?;\n    for row in rows {{\n        println!(\"{{:?}}\", row);\n    }}\n\n",
+            sanitize_ident(&query_def.name)
+        ));
     }
 
+    let binding = match (schema.procs.is_empty(), schema.queries.is_empty()) {
+        (false, _) => "mut db",
+        (true, false) => "db",
+        (true, true) => "_db",
+    };
     Ok(format!(
-        "use anyhow::Result;\nuse {}::Db;\n\nfn main() -> Result<()> {{\n    let mut db = Db::new()?;\n{}\n    let rows = db.{}()?;\n    for row in rows {{\n        println!(\"{{:?}}\", row);\n    }}\n\n    Ok(())\n}}\n",
-        crate_name,
-        demo_calls,
-        query_name
+        "use {}::{{Db, Error}};\n\nfn main() -> Result<(), Error> {{\n    let {} = Db::new()?;\n\n{}    Ok(())\n}}\n",
+        db_path, binding, body
     ))
 }
 
@@ -257,7 +1196,40 @@ fn demo_value(
     param: &crate::ir::schema::ProcParamIr,
     row_index: usize,
     param_index: usize,
+    schema: &ResolvedSchema,
+    db_path: &str,
+) -> Result<String, Error> {
+    if param_has_default(param, schema) {
+        // Alternate between an explicit value and the column default.
+        if row_index % 2 == 1 {
+            return Ok("None".to_string());
+        }
+        let value =
+            demo_scalar_value(param, row_index, param_index, schema, db_path)?;
+        return Ok(format!("Some({})", value));
+    }
+
+    demo_scalar_value(param, row_index, param_index, schema, db_path)
+}
+
+fn demo_scalar_value(
+    param: &crate::ir::schema::ProcParamIr,
+    row_index: usize,
+    param_index: usize,
+    schema: &ResolvedSchema,
+    db_path: &str,
 ) -> Result<String, Error> {
+    if let Some(enum_def) = schema.enum_type(&param.ty) {
+        let variant = &enum_def.variants
+            [(row_index + param_index) % enum_def.variants.len()];
+        return Ok(format!(
+            "{}::{}::{}",
+            db_path,
+            pascal_case(&enum_def.name),
+            pascal_case(variant)
+        ));
+    }
+
     match param.ty.as_str() {
         "i64" => Ok(((row_index + param_index + 1) as i64).to_string()),
         "text" => Ok(format!(
@@ -318,6 +1290,38 @@ fn sanitize_ident(value: &str) -> String {
     }
 }
 
+fn pascal_case(value: &str) -> String {
+    let mut out = String::new();
+    let mut upper_next = true;
+    for ch in value.chars() {
+        if ch.is_ascii_alphanumeric() {
+            if upper_next {
+                out.push(ch.to_ascii_uppercase());
+            } else {
+                out.push(ch);
+            }
+            upper_next = false;
+        } else {
+            upper_next = true;
+        }
+    }
+
+    if out.is_empty() {
+        return "Generated".to_string();
+    }
+
+    let first_is_ok = out
+        .chars()
+        .next()
+        .map(|ch| ch.is_ascii_alphabetic())
+        .unwrap_or(false);
+    if first_is_ok {
+        out
+    } else {
+        format!("_{}", out)
+    }
+}
+
 fn escape_rust_string(value: &str) -> String {
     value.replace('\\', "\\\\").replace('"', "\\\"")
 }
@@ -0,0 +1,263 @@
+use crate::build::result_tuple_type;
+use crate::diff::{self, diff_schemas, ChangeKind, ItemKind, SchemaChange};
+use crate::error::Error;
+use crate::ir::schema::{FieldIr, ProcParamIr, ResolvedSchema};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Compatibility {
+    Breaking,
+    Additive,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CompatChange {
+    pub change: SchemaChange,
+    pub compatibility: Compatibility,
+    pub reason: String,
+}
+
+impl Compatibility {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Compatibility::Breaking => "breaking",
+            Compatibility::Additive => "additive",
+        }
+    }
+}
+
+// Classifies every change between two schemas by its effect on callers of
+// the generated `Db` crate.
+pub fn check_compat(
+    old: &ResolvedSchema,
+    new: &ResolvedSchema,
+) -> Result<Vec<CompatChange>, Error> {
+    diff_schemas(old, new)
+        .into_iter()
+        .map(|change| {
+            let (compatibility, reason) = classify(&change, old, new)?;
+            Ok(CompatChange {
+                change,
+                compatibility,
+                reason,
+            })
+        })
+        .collect()
+}
+
+fn classify(
+    change: &SchemaChange,
+    old: &ResolvedSchema,
+    new: &ResolvedSchema,
+) -> Result<(Compatibility, String), Error> {
+    use Compatibility::{Additive, Breaking};
+
+    let classified = match (change.item, change.kind) {
+        (ItemKind::Enum, ChangeKind::Added) => (Additive, "new enum type"),
+        (ItemKind::Enum, ChangeKind::Removed) => {
+            (Breaking, "removed enum type")
+        }
+        (ItemKind::Enum, ChangeKind::Retyped) => (
+            Breaking,
+            "changed enum variants or representation; exhaustive matches and stored values may break",
+        ),
+        (ItemKind::Table, ChangeKind::Removed) => {
+            (Breaking, "removed table and its stored rows")
+        }
+        (ItemKind::Table, ChangeKind::Added) => (Additive, "new table"),
+        // Fields are compared on their own, so a retyped table means its
+        // table-level checks changed.
+        (ItemKind::Table, ChangeKind::Retyped) => {
+            (Breaking, "changed table checks; inserts may now fail")
+        }
+        (ItemKind::Field, ChangeKind::Added) => (Additive, "new column"),
+        (ItemKind::Field, ChangeKind::Removed) => {
+            (Breaking, "removed column")
+        }
+        (ItemKind::Field, ChangeKind::Retyped) => {
+            let old_field = find_field(old, &change.name)?;
+            let new_field = find_field(new, &change.name)?;
+            if old_field.ty != new_field.ty {
+                (Breaking, "changed column type")
+            } else if old_field.default != new_field.default
+                || old_field.auto_increment != new_field.auto_increment
+                || old_field.computed != new_field.computed
+            {
+                // These decide whether a proc takes the column as `T` or
+                // `Option<T>`, or takes it at all.
+                if is_param_column(old, old_field)
+                    || is_param_column(new, new_field)
+                {
+                    (
+                        Breaking,
+                        "changed default, auto-increment or computed on a proc param column",
+                    )
+                } else {
+                    (Additive, "changed column default or computed value")
+                }
+            } else {
+                (Breaking, "changed key or checks; inserts may now fail")
+            }
+        }
+        (ItemKind::Proc, ChangeKind::Added) => (Additive, "new proc method"),
+        (ItemKind::Proc, ChangeKind::Removed) => {
+            (Breaking, "removed proc method")
+        }
+        (ItemKind::Proc, ChangeKind::Retyped) => {
+            (Breaking, "proc now inserts into a different table")
+        }
+        (ItemKind::ProcParam, ChangeKind::Added) => {
+            let (proc_name, param_name) =
+                change.name.rsplit_once('.').unwrap_or_default();
+            if param_has_default(new, proc_name, param_name) {
+                (
+                    Breaking,
+                    "new optional proc param; callers must pass None",
+                )
+            } else {
+                (Breaking, "new required proc param")
+            }
+        }
+        (ItemKind::ProcParam, ChangeKind::Removed) => {
+            (Breaking, "removed proc param")
+        }
+        (ItemKind::ProcParam, ChangeKind::Retyped) => {
+            let (old_position, old_param) = find_param(old, &change.name)?;
+            let (new_position, new_param) = find_param(new, &change.name)?;
+            if old_param.ty != new_param.ty {
+                (Breaking, "changed proc param type")
+            } else {
+                return Ok((
+                    Breaking,
+                    format!(
+                        "moved proc param from position {} to {}",
+                        old_position + 1,
+                        new_position + 1
+                    ),
+                ));
+            }
+        }
+        (ItemKind::Query, ChangeKind::Added) => {
+            (Additive, "new query method")
+        }
+        (ItemKind::Query, ChangeKind::Removed) => {
+            (Breaking, "removed query method")
+        }
+        (ItemKind::Query, ChangeKind::Retyped) => {
+            let old_shape = query_tuple_type(old, &change.name)?;
+            let new_shape = query_tuple_type(new, &change.name)?;
+            if old_shape == new_shape {
+                return Ok((
+                    Additive,
+                    format!("same tuple shape {}", new_shape),
+                ));
+            }
+            return Ok((
+                Breaking,
+                format!(
+                    "changed tuple shape from {} to {}",
+                    old_shape, new_shape
+                ),
+            ));
+        }
+    };
+
+    Ok((classified.0, classified.1.to_string()))
+}
+
+fn param_has_default(
+    schema: &ResolvedSchema,
+    proc_name: &str,
+    param_name: &str,
+) -> bool {
+    schema
+        .procs
+        .iter()
+        .find(|proc_def| proc_def.name == proc_name)
+        .and_then(|proc_def| {
+            proc_def
+                .params
+                .iter()
+                .find(|param| param.name == param_name)
+        })
+        .and_then(|param| schema.column(param.column))
+        .map(|column| column.default.is_some())
+        .unwrap_or(false)
+}
+
+fn find_field<'a>(
+    schema: &'a ResolvedSchema,
+    name: &str,
+) -> Result<&'a FieldIr, Error> {
+    let (table_name, field_name) = name.rsplit_once('.').unwrap_or_default();
+    schema
+        .tables
+        .iter()
+        .find(|table| table.name == table_name)
+        .and_then(|table| {
+            table.fields.iter().find(|field| field.name == field_name)
+        })
+        .ok_or_else(|| Error::Pass(format!("unknown field '{}'", name)))
+}
+
+fn find_param<'a>(
+    schema: &'a ResolvedSchema,
+    name: &str,
+) -> Result<(usize, &'a ProcParamIr), Error> {
+    let (proc_name, param_name) = name.rsplit_once('.').unwrap_or_default();
+    schema
+        .procs
+        .iter()
+        .find(|proc_def| proc_def.name == proc_name)
+        .and_then(|proc_def| {
+            proc_def
+                .params
+                .iter()
+                .enumerate()
+                .find(|(_, param)| param.name == param_name)
+        })
+        .ok_or_else(|| Error::Pass(format!("unknown proc param '{}'", name)))
+}
+
+fn is_param_column(schema: &ResolvedSchema, field: &FieldIr) -> bool {
+    schema.procs.iter().any(|proc_def| {
+        proc_def.params.iter().any(|param| param.column == field.id)
+    })
+}
+
+fn query_tuple_type(
+    schema: &ResolvedSchema,
+    query_name: &str,
+) -> Result<String, Error> {
+    let query = schema
+        .queries
+        .iter()
+        .find(|query| query.name == query_name)
+        .ok_or_else(|| {
+            Error::Pass(format!("unknown query '{}'", query_name))
+        })?;
+    result_tuple_type(&query.name, &query.projection, schema)
+}
+
+pub fn render_text(changes: &[CompatChange]) -> String {
+    let mut out = String::new();
+    for change in changes {
+        let line = diff::render_text(std::slice::from_ref(&change.change));
+        out.push_str(&format!(
+            "{}: {} ({})\n",
+            change.compatibility.as_str(),
+            line.trim_end(),
+            change.reason
+        ));
+    }
+
+    let breaking = changes
+        .iter()
+        .filter(|change| change.compatibility == Compatibility::Breaking)
+        .count();
+    out.push_str(&format!(
+        "{} breaking, {} additive\n",
+        breaking,
+        changes.len() - breaking
+    ));
+    out
+}
@@ -0,0 +1,354 @@
+use crate::ir::schema::{
+    EnumIr, FieldIr, ProcIr, ProcParamIr, QueryIr, ResolvedSchema, TableIr,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Retyped,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ItemKind {
+    Enum,
+    Table,
+    Field,
+    Proc,
+    ProcParam,
+    Query,
+}
+
+// One difference between two resolved schemas. Nested items are named
+// with their parent, e.g. `people.age` for a field or `add_person.age` for
+// a proc param. `old` and `new` describe the item on each side: its type
+// plus whatever else the generated code depends on, such as a table's
+// checks, a field's key, default, computed expression and checks, a
+// param's position, or a query's columns and filter. Any difference between them is reported as `Retyped`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SchemaChange {
+    pub kind: ChangeKind,
+    pub item: ItemKind,
+    pub name: String,
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+impl ChangeKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChangeKind::Added => "added",
+            ChangeKind::Removed => "removed",
+            ChangeKind::Retyped => "retyped",
+        }
+    }
+
+    fn marker(&self) -> char {
+        match self {
+            ChangeKind::Added => '+',
+            ChangeKind::Removed => '-',
+            ChangeKind::Retyped => '~',
+        }
+    }
+}
+
+impl ItemKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ItemKind::Enum => "enum",
+            ItemKind::Table => "table",
+            ItemKind::Field => "field",
+            ItemKind::Proc => "proc",
+            ItemKind::ProcParam => "param",
+            ItemKind::Query => "query",
+        }
+    }
+}
+
+pub fn diff_schemas(
+    old: &ResolvedSchema,
+    new: &ResolvedSchema,
+) -> Vec<SchemaChange> {
+    let mut changes = Vec::new();
+
+    diff_items(
+        &mut changes,
+        ItemKind::Enum,
+        (old, &old.enums),
+        (new, &new.enums),
+        |enum_def| enum_def.name.clone(),
+        |enum_def, _| Some(describe_enum(enum_def)),
+    );
+
+    diff_items(
+        &mut changes,
+        ItemKind::Table,
+        (old, &old.tables),
+        (new, &new.tables),
+        |table| table.name.clone(),
+        |table, _| describe_table(table),
+    );
+    for new_table in &new.tables {
+        if let Some(old_table) = find_table(old, &new_table.name) {
+            diff_items(
+                &mut changes,
+                ItemKind::Field,
+                (old, &old_table.fields),
+                (new, &new_table.fields),
+                |field| format!("{}.{}", new_table.name, field.name),
+                |field, _| Some(describe_field(field)),
+            );
+        }
+    }
+
+    diff_items(
+        &mut changes,
+        ItemKind::Proc,
+        (old, &old.procs),
+        (new, &new.procs),
+        |proc_def| proc_def.name.clone(),
+        |proc_def, schema| Some(describe_proc(proc_def, schema)),
+    );
+    for new_proc in &new.procs {
+        if let Some(old_proc) = old
+            .procs
+            .iter()
+            .find(|proc_def| proc_def.name == new_proc.name)
+        {
+            diff_items(
+                &mut changes,
+                ItemKind::ProcParam,
+                (old, &old_proc.params),
+                (new, &new_proc.params),
+                |param| format!("{}.{}", new_proc.name, param.name),
+                |param, schema| describe_param(&new_proc.name, param, schema),
+            );
+        }
+    }
+
+    diff_items(
+        &mut changes,
+        ItemKind::Query,
+        (old, &old.queries),
+        (new, &new.queries),
+        |query| query.name.clone(),
+        |query, schema| Some(describe_query(query, schema)),
+    );
+
+    changes
+}
+
+// Items are matched by name. Removed items are reported in the old
+// schema's order, then added and retyped items in the new schema's order.
+// `describe` is given the schema the item belongs to, since procs and
+// queries refer to tables and columns by id.
+fn diff_items<T>(
+    changes: &mut Vec<SchemaChange>,
+    item: ItemKind,
+    (old_schema, old): (&ResolvedSchema, &[T]),
+    (new_schema, new): (&ResolvedSchema, &[T]),
+    name: impl Fn(&T) -> String,
+    describe: impl Fn(&T, &ResolvedSchema) -> Option<String>,
+) {
+    for old_item in old {
+        let old_name = name(old_item);
+        if !new.iter().any(|new_item| name(new_item) == old_name) {
+            changes.push(SchemaChange {
+                kind: ChangeKind::Removed,
+                item,
+                name: old_name,
+                old: describe(old_item, old_schema),
+                new: None,
+            });
+        }
+    }
+
+    for new_item in new {
+        let new_name = name(new_item);
+        match old.iter().find(|old_item| name(old_item) == new_name) {
+            None => changes.push(SchemaChange {
+                kind: ChangeKind::Added,
+                item,
+                name: new_name,
+                old: None,
+                new: describe(new_item, new_schema),
+            }),
+            Some(old_item) => {
+                let old_desc = describe(old_item, old_schema);
+                let new_desc = describe(new_item, new_schema);
+                if old_desc != new_desc {
+                    changes.push(SchemaChange {
+                        kind: ChangeKind::Retyped,
+                        item,
+                        name: new_name,
+                        old: old_desc,
+                        new: new_desc,
+                    });
+                }
+            }
+        }
+    }
+}
+
+fn describe_enum(enum_def: &EnumIr) -> String {
+    format!("{} [{}]", enum_def.repr, enum_def.variants.join(", "))
+}
+
+// Fields are compared one by one, so a table is described only by its
+// table-level checks, and not at all if it has none.
+fn describe_table(table: &TableIr) -> Option<String> {
+    if table.checks.is_empty() {
+        return None;
+    }
+    let checks = table
+        .checks
+        .iter()
+        .map(|check| format!("check {} {}", check.name, check.expr))
+        .collect::<Vec<_>>();
+    Some(checks.join(", "))
+}
+
+fn describe_field(field: &FieldIr) -> String {
+    let mut parts = vec![field.ty.clone()];
+    if field.key {
+        parts.push("key".to_string());
+    }
+    if field.auto_increment {
+        parts.push("auto-increment".to_string());
+    }
+    if let Some(default) = &field.default {
+        parts.push(format!("default {}", default));
+    }
+    if let Some(computed) = &field.computed {
+        parts.push(format!("computed {}", computed));
+    }
+    for check in &field.checks {
+        parts.push(format!("check {} {}", check.name, check.expr));
+    }
+    parts.join(", ")
+}
+
+// Params are passed positionally, so the description includes the 1-based
+// position among the proc's params.
+fn describe_param(
+    proc_name: &str,
+    param: &ProcParamIr,
+    schema: &ResolvedSchema,
+) -> Option<String> {
+    let position = schema
+        .procs
+        .iter()
+        .find(|proc_def| proc_def.name == proc_name)?
+        .params
+        .iter()
+        .position(|other| other.name == param.name)?;
+    Some(format!("{} #{}", param.ty, position + 1))
+}
+
+fn describe_proc(proc_def: &ProcIr, schema: &ResolvedSchema) -> String {
+    schema
+        .table(proc_def.table)
+        .map(|table| format!("insert into {}", table.name))
+        .unwrap_or_default()
+}
+
+fn describe_query(query: &QueryIr, schema: &ResolvedSchema) -> String {
+    let table = schema
+        .table(query.table)
+        .map(|table| table.name.as_str())
+        .unwrap_or_default();
+    let columns = query
+        .projection
+        .iter()
+        .map(|column_id| {
+            schema
+                .column(*column_id)
+                .map(|field| format!("{}: {}", field.name, field.ty))
+                .unwrap_or_default()
+        })
+        .collect::<Vec<_>>();
+    match &query.filter {
+        Some(filter) => {
+            format!("{} ({}) where {}", table, columns.join(", "), filter)
+        }
+        None => format!("{} ({})", table, columns.join(", ")),
+    }
+}
+
+fn find_table<'a>(
+    schema: &'a ResolvedSchema,
+    name: &str,
+) -> Option<&'a TableIr> {
+    schema.tables.iter().find(|table| table.name == name)
+}
+
+pub fn render_text(changes: &[SchemaChange]) -> String {
+    let mut out = String::new();
+    for change in changes {
+        out.push_str(&format!(
+            "{} {} {}",
+            change.kind.marker(),
+            change.item.as_str(),
+            change.name
+        ));
+        match (&change.old, &change.new) {
+            (Some(old), Some(new)) => {
+                out.push_str(&format!(": {} -> {}", old, new))
+            }
+            (Some(ty), None) | (None, Some(ty)) => {
+                out.push_str(&format!(": {}", ty))
+            }
+            (None, None) => {}
+        }
+        out.push('\n');
+    }
+    out
+}
+
+pub fn render_json(changes: &[SchemaChange]) -> String {
+    let mut out = String::from("{\n  \"changes\": [");
+    for (index, change) in changes.iter().enumerate() {
+        if index > 0 {
+            out.push(',');
+        }
+        out.push_str(&format!(
+            "\n    {{\"change\": {}, \"item\": {}, \"name\": {}, \"old\": {}, \"new\": {}}}",
+            json_string(change.kind.as_str()),
+            json_string(change.item.as_str()),
+            json_string(&change.name),
+            json_optional_string(&change.old),
+            json_optional_string(&change.new)
+        ));
+    }
+    if !changes.is_empty() {
+        out.push_str("\n  ");
+    }
+    out.push_str("]\n}\n");
+    out
+}
+
+fn json_optional_string(value: &Option<String>) -> String {
+    match value {
+        Some(value) => json_string(value),
+        None => "null".to_string(),
+    }
+}
+
+fn json_string(value: &str) -> String {
+    let mut out = String::from("\"");
+    for ch in value.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                out.push_str(&format!("\\u{:04x}", c as u32))
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
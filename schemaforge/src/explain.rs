@@ -0,0 +1,89 @@
+use crate::backend::sqlite::{compile_plan_to_sql, SqlQuery};
+use crate::error::Error;
+use crate::ir::schema::ResolvedSchema;
+use crate::lower::LoweredQuery;
+use crate::plan::{ColumnId, Plan};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QueryExplanation {
+    pub name: String,
+    pub plan: Plan,
+    pub sql: SqlQuery,
+}
+
+pub fn explain_queries(
+    schema: &ResolvedSchema,
+    lowered: &[LoweredQuery],
+    only: Option<&str>,
+) -> Result<Vec<QueryExplanation>, Error> {
+    if let Some(name) = only {
+        if !lowered.iter().any(|query| query.name == name) {
+            return Err(Error::Pass(format!("unknown query '{}'", name)));
+        }
+    }
+
+    lowered
+        .iter()
+        .filter(|query| only.map(|name| query.name == name).unwrap_or(true))
+        .map(|query| {
+            Ok(QueryExplanation {
+                name: query.name.clone(),
+                plan: query.plan.clone(),
+                sql: compile_plan_to_sql(&query.plan, schema)?,
+            })
+        })
+        .collect()
+}
+
+pub fn render_plan_tree(plan: &Plan, schema: &ResolvedSchema) -> String {
+    let mut out = String::new();
+    render_plan_node(plan, schema, 0, &mut out);
+    out
+}
+
+fn render_plan_node(
+    plan: &Plan,
+    schema: &ResolvedSchema,
+    depth: usize,
+    out: &mut String,
+) {
+    out.push_str(&"  ".repeat(depth));
+    match plan {
+        Plan::TableScan { table } => {
+            out.push_str(&format!(
+                "TableScan {}\n",
+                table_name(schema, *table)
+            ));
+        }
+        Plan::Filter { input, predicate } => {
+            out.push_str(&format!("Filter {}\n", predicate));
+            render_plan_node(input, schema, depth + 1, out);
+        }
+        Plan::Project { input, columns } => {
+            let names = columns
+                .iter()
+                .map(|column_id| qualified_column_name(schema, *column_id))
+                .collect::<Vec<_>>();
+            out.push_str(&format!("Project {}\n", names.join(", ")));
+            render_plan_node(input, schema, depth + 1, out);
+        }
+    }
+}
+
+fn table_name(schema: &ResolvedSchema, table_id: usize) -> String {
+    schema
+        .table(table_id)
+        .map(|table| table.name.clone())
+        .unwrap_or_else(|| format!("<table {}>", table_id))
+}
+
+fn qualified_column_name(
+    schema: &ResolvedSchema,
+    column_id: ColumnId,
+) -> String {
+    let column = schema
+        .column(column_id)
+        .map(|field| field.name.clone())
+        .unwrap_or_else(|| format!("<column {}>", column_id.column));
+    format!("{}.{}", table_name(schema, column_id.table), column)
+}
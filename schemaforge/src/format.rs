@@ -0,0 +1,737 @@
+use crate::error::Error;
+use crate::ir::ast::parse_kdl;
+
+// Canonical property order for the nodes schemaforge understands. Positional
+// values always come first; properties not listed here keep their relative
+// order after the listed ones.
+const PROPERTY_ORDER: [(&str, &[&str]); 6] = [
+    ("enum", &["repr"]),
+    (
+        "field",
+        &["type", "key", "auto-increment", "default", "computed"],
+    ),
+    ("check", &["name"]),
+    ("proc", &["table"]),
+    ("param", &["type"]),
+    ("query", &["table"]),
+];
+
+const INDENT: &str = "    ";
+
+// Reformats KDL schema source: one node per line, four-space indentation,
+// canonical property order and quoting. Unlike `ir::ast::print_kdl`, comments
+// and declaration order are preserved; runs of blank lines collapse to one.
+// The source must parse as a schema first, so `fmt` never rewrites a file
+// the loader would reject.
+pub fn format_schema(src: &str) -> Result<String, Error> {
+    parse_kdl(src)?;
+    let tokens = lex(src)?;
+    let mut parser = Parser { tokens, pos: 0 };
+    let items = parser.parse_items(false)?;
+
+    let mut out = String::new();
+    print_items(&items, 0, &mut out);
+    Ok(out)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Ident(String),
+    Str(String),
+    // Numbers, booleans and null are kept as written.
+    Literal(String),
+    LineComment(String),
+    BlockComment(String),
+    SlashDash,
+    LBrace,
+    RBrace,
+    Semi,
+    Eq,
+    Newline,
+}
+
+#[derive(Debug)]
+enum Item {
+    Comment { text: String, blank_before: bool },
+    Node(Node),
+}
+
+#[derive(Debug)]
+struct Node {
+    blank_before: bool,
+    slashdash: bool,
+    name: String,
+    entries: Vec<Entry>,
+    children: Option<Children>,
+    trailing_comment: Option<String>,
+}
+
+#[derive(Debug)]
+struct Children {
+    open_comment: Option<String>,
+    items: Vec<Item>,
+}
+
+#[derive(Debug)]
+enum Entry {
+    Arg {
+        slashdash: bool,
+        value: String,
+    },
+    Prop {
+        slashdash: bool,
+        key: String,
+        value: String,
+    },
+    Comment(String),
+}
+
+fn lex(src: &str) -> Result<Vec<Token>, Error> {
+    let chars = src.chars().collect::<Vec<_>>();
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+
+    while pos < chars.len() {
+        let ch = chars[pos];
+        match ch {
+            '\n' => {
+                tokens.push(Token::Newline);
+                pos += 1;
+            }
+            '\r' => {
+                tokens.push(Token::Newline);
+                pos += 1;
+                if chars.get(pos) == Some(&'\n') {
+                    pos += 1;
+                }
+            }
+            c if c.is_whitespace() || c == '\u{feff}' => pos += 1,
+            '{' => {
+                tokens.push(Token::LBrace);
+                pos += 1;
+            }
+            '}' => {
+                tokens.push(Token::RBrace);
+                pos += 1;
+            }
+            ';' => {
+                tokens.push(Token::Semi);
+                pos += 1;
+            }
+            '=' => {
+                tokens.push(Token::Eq);
+                pos += 1;
+            }
+            '\\' => {
+                // Line continuation: the node carries on past the newline.
+                pos += 1;
+                while pos < chars.len()
+                    && chars[pos].is_whitespace()
+                    && !matches!(chars[pos], '\n' | '\r')
+                {
+                    pos += 1;
+                }
+                if chars.get(pos) == Some(&'/')
+                    && chars.get(pos + 1) == Some(&'/')
+                {
+                    return Err(Error::Parse(
+                        "fmt does not support comments after a line continuation"
+                            .into(),
+                    ));
+                }
+                match chars.get(pos) {
+                    Some('\r') => {
+                        pos += 1;
+                        if chars.get(pos) == Some(&'\n') {
+                            pos += 1;
+                        }
+                    }
+                    Some('\n') => pos += 1,
+                    None => {}
+                    Some(_) => {
+                        return Err(Error::Parse(
+                            "line continuation must be followed by a newline"
+                                .into(),
+                        ))
+                    }
+                }
+            }
+            '/' => match chars.get(pos + 1) {
+                Some('/') => {
+                    let start = pos;
+                    while pos < chars.len()
+                        && !matches!(chars[pos], '\n' | '\r')
+                    {
+                        pos += 1;
+                    }
+                    let text = chars[start..pos].iter().collect::<String>();
+                    tokens
+                        .push(Token::LineComment(text.trim_end().to_string()));
+                }
+                Some('*') => {
+                    let start = pos;
+                    let mut depth = 0;
+                    loop {
+                        if pos >= chars.len() {
+                            return Err(Error::Parse(
+                                "unterminated block comment".into(),
+                            ));
+                        }
+                        if chars[pos] == '/' && chars.get(pos + 1) == Some(&'*')
+                        {
+                            depth += 1;
+                            pos += 2;
+                        } else if chars[pos] == '*'
+                            && chars.get(pos + 1) == Some(&'/')
+                        {
+                            depth -= 1;
+                            pos += 2;
+                            if depth == 0 {
+                                break;
+                            }
+                        } else {
+                            pos += 1;
+                        }
+                    }
+                    tokens.push(Token::BlockComment(
+                        chars[start..pos].iter().collect(),
+                    ));
+                }
+                Some('-') => {
+                    tokens.push(Token::SlashDash);
+                    pos += 2;
+                }
+                _ => {
+                    return Err(Error::Parse(
+                        "unexpected '/' in schema source".into(),
+                    ))
+                }
+            },
+            '"' => {
+                let (value, end) = lex_string(&chars, pos + 1)?;
+                tokens.push(Token::Str(value));
+                pos = end;
+            }
+            'r' if matches!(chars.get(pos + 1), Some('"') | Some('#')) => {
+                let (value, end) = lex_raw_string(&chars, pos + 1)?;
+                tokens.push(Token::Str(value));
+                pos = end;
+            }
+            '(' => {
+                return Err(Error::Parse(
+                    "fmt does not support type annotations".into(),
+                ))
+            }
+            _ => {
+                let start = pos;
+                while pos < chars.len() && !is_delimiter(chars[pos]) {
+                    pos += 1;
+                }
+                if start == pos {
+                    return Err(Error::Parse(format!(
+                        "unexpected '{}' in schema source",
+                        ch
+                    )));
+                }
+                let word = chars[start..pos].iter().collect::<String>();
+                if is_number_like(&word)
+                    || matches!(word.as_str(), "true" | "false" | "null")
+                {
+                    tokens.push(Token::Literal(word));
+                } else {
+                    tokens.push(Token::Ident(word));
+                }
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+fn is_delimiter(ch: char) -> bool {
+    ch.is_whitespace()
+        || ch == '\u{feff}'
+        || matches!(
+            ch,
+            '\\' | '/'
+                | '('
+                | ')'
+                | '{'
+                | '}'
+                | '<'
+                | '>'
+                | ';'
+                | '['
+                | ']'
+                | '='
+                | ','
+                | '"'
+        )
+}
+
+fn lex_string(
+    chars: &[char],
+    mut pos: usize,
+) -> Result<(String, usize), Error> {
+    let mut value = String::new();
+    loop {
+        match chars.get(pos) {
+            None => {
+                return Err(Error::Parse("unterminated string".into()));
+            }
+            Some('"') => return Ok((value, pos + 1)),
+            Some('\\') => {
+                let escaped = match chars.get(pos + 1) {
+                    Some('n') => '\n',
+                    Some('r') => '\r',
+                    Some('t') => '\t',
+                    Some('\\') => '\\',
+                    Some('/') => '/',
+                    Some('"') => '"',
+                    Some('b') => '\u{08}',
+                    Some('f') => '\u{0c}',
+                    Some('u') if chars.get(pos + 2) == Some(&'{') => {
+                        let start = pos + 3;
+                        let end = (start..chars.len())
+                            .find(|index| chars[*index] == '}')
+                            .ok_or_else(|| {
+                                Error::Parse(
+                                    "unterminated unicode escape".into(),
+                                )
+                            })?;
+                        let hex = chars[start..end].iter().collect::<String>();
+                        let ch = u32::from_str_radix(&hex, 16)
+                            .ok()
+                            .and_then(char::from_u32)
+                            .ok_or_else(|| {
+                                Error::Parse(format!(
+                                    "invalid unicode escape '\\u{{{}}}'",
+                                    hex
+                                ))
+                            })?;
+                        value.push(ch);
+                        pos = end + 1;
+                        continue;
+                    }
+                    _ => {
+                        return Err(Error::Parse(
+                            "invalid escape in string".into(),
+                        ))
+                    }
+                };
+                value.push(escaped);
+                pos += 2;
+            }
+            Some(ch) => {
+                value.push(*ch);
+                pos += 1;
+            }
+        }
+    }
+}
+
+fn lex_raw_string(
+    chars: &[char],
+    mut pos: usize,
+) -> Result<(String, usize), Error> {
+    let mut hashes = 0;
+    while chars.get(pos) == Some(&'#') {
+        hashes += 1;
+        pos += 1;
+    }
+    if chars.get(pos) != Some(&'"') {
+        return Err(Error::Parse("invalid raw string".into()));
+    }
+    pos += 1;
+
+    let start = pos;
+    while pos < chars.len() {
+        if chars[pos] == '"'
+            && (1..=hashes).all(|offset| chars.get(pos + offset) == Some(&'#'))
+        {
+            let value = chars[start..pos].iter().collect();
+            return Ok((value, pos + 1 + hashes));
+        }
+        pos += 1;
+    }
+    Err(Error::Parse("unterminated raw string".into()))
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn parse_items(&mut self, in_block: bool) -> Result<Vec<Item>, Error> {
+        let mut items = Vec::new();
+        let mut newlines = 0;
+        let mut slashdash = false;
+
+        loop {
+            // A blank line is two newlines since the end of the last item.
+            let blank_before = !items.is_empty() && newlines >= 2;
+            match self.peek() {
+                None if in_block => {
+                    return Err(Error::Parse(
+                        "unterminated children block".into(),
+                    ))
+                }
+                None => return Ok(items),
+                Some(Token::RBrace) if in_block => {
+                    self.pos += 1;
+                    return Ok(items);
+                }
+                Some(Token::RBrace) => {
+                    return Err(Error::Parse("unexpected '}'".into()))
+                }
+                Some(Token::Newline) => {
+                    self.pos += 1;
+                    newlines += 1;
+                }
+                Some(Token::Semi) => self.pos += 1,
+                Some(Token::LineComment(text))
+                | Some(Token::BlockComment(text)) => {
+                    items.push(Item::Comment {
+                        text: text.clone(),
+                        blank_before,
+                    });
+                    self.pos += 1;
+                    newlines = 0;
+                }
+                Some(Token::SlashDash) => {
+                    self.pos += 1;
+                    slashdash = true;
+                }
+                Some(_) => {
+                    let mut node = self.parse_node(blank_before)?;
+                    node.slashdash = slashdash;
+                    slashdash = false;
+                    items.push(Item::Node(node));
+                    newlines = 0;
+                }
+            }
+        }
+    }
+
+    fn parse_node(&mut self, blank_before: bool) -> Result<Node, Error> {
+        let name = match self.next() {
+            Some(Token::Ident(name)) | Some(Token::Str(name)) => name,
+            other => {
+                return Err(Error::Parse(format!(
+                    "expected a node name, found {}",
+                    describe(other.as_ref())
+                )))
+            }
+        };
+        let mut node = Node {
+            blank_before,
+            slashdash: false,
+            name,
+            entries: Vec::new(),
+            children: None,
+            trailing_comment: None,
+        };
+
+        let mut slashdash = false;
+        loop {
+            match self.peek() {
+                None | Some(Token::RBrace) | Some(Token::Newline) => break,
+                Some(Token::Semi) => {
+                    self.pos += 1;
+                    break;
+                }
+                Some(Token::LineComment(text)) => {
+                    node.trailing_comment = Some(text.clone());
+                    self.pos += 1;
+                    break;
+                }
+                Some(Token::BlockComment(text)) => {
+                    node.entries.push(Entry::Comment(text.clone()));
+                    self.pos += 1;
+                }
+                Some(Token::SlashDash) => {
+                    self.pos += 1;
+                    slashdash = true;
+                }
+                Some(Token::LBrace) => {
+                    if slashdash {
+                        return Err(Error::Parse(
+                            "fmt does not support slashdash on children blocks"
+                                .into(),
+                        ));
+                    }
+                    self.pos += 1;
+                    node.children = Some(self.parse_children()?);
+                    // The children block ends the node; a comment may follow
+                    // the closing brace on the same line.
+                    match self.peek() {
+                        Some(Token::LineComment(text)) => {
+                            node.trailing_comment = Some(text.clone());
+                            self.pos += 1;
+                        }
+                        Some(Token::Semi) => self.pos += 1,
+                        None | Some(Token::RBrace) | Some(Token::Newline) => {}
+                        Some(other) => {
+                            return Err(Error::Parse(format!(
+                                "unexpected {} after children block of '{}'",
+                                describe(Some(other)),
+                                node.name
+                            )))
+                        }
+                    }
+                    break;
+                }
+                Some(_) => {
+                    let entry = self.parse_entry(&node.name, slashdash)?;
+                    node.entries.push(entry);
+                    slashdash = false;
+                }
+            }
+        }
+
+        if slashdash {
+            return Err(Error::Parse(format!(
+                "dangling slashdash in node '{}'",
+                node.name
+            )));
+        }
+        Ok(node)
+    }
+
+    fn parse_children(&mut self) -> Result<Children, Error> {
+        let open_comment = match self.peek() {
+            Some(Token::LineComment(text)) => {
+                let text = text.clone();
+                self.pos += 1;
+                Some(text)
+            }
+            _ => None,
+        };
+        let items = self.parse_items(true)?;
+        Ok(Children {
+            open_comment,
+            items,
+        })
+    }
+
+    fn parse_entry(
+        &mut self,
+        node_name: &str,
+        slashdash: bool,
+    ) -> Result<Entry, Error> {
+        let token = self.next();
+        if self.peek() == Some(&Token::Eq) {
+            let key = match token {
+                Some(Token::Ident(key)) | Some(Token::Str(key)) => key,
+                other => {
+                    return Err(Error::Parse(format!(
+                        "invalid property name {} in node '{}'",
+                        describe(other.as_ref()),
+                        node_name
+                    )))
+                }
+            };
+            self.pos += 1;
+            let value = self.next();
+            let value = print_value(value.as_ref()).ok_or_else(|| {
+                Error::Parse(format!(
+                    "property '{}' in node '{}' has no value",
+                    key, node_name
+                ))
+            })?;
+            return Ok(Entry::Prop {
+                slashdash,
+                key,
+                value,
+            });
+        }
+
+        let value = print_value(token.as_ref()).ok_or_else(|| {
+            Error::Parse(format!(
+                "unexpected {} in node '{}'",
+                describe(token.as_ref()),
+                node_name
+            ))
+        })?;
+        Ok(Entry::Arg { slashdash, value })
+    }
+}
+
+fn describe(token: Option<&Token>) -> String {
+    match token {
+        None => "end of input".to_string(),
+        Some(Token::Ident(word)) => format!("identifier '{}'", word),
+        Some(Token::Str(value)) => format!("string {}", quote(value)),
+        Some(Token::Literal(value)) => format!("'{}'", value),
+        Some(Token::LineComment(_)) | Some(Token::BlockComment(_)) => {
+            "comment".to_string()
+        }
+        Some(Token::SlashDash) => "'/-'".to_string(),
+        Some(Token::LBrace) => "'{'".to_string(),
+        Some(Token::RBrace) => "'}'".to_string(),
+        Some(Token::Semi) => "';'".to_string(),
+        Some(Token::Eq) => "'='".to_string(),
+        Some(Token::Newline) => "newline".to_string(),
+    }
+}
+
+fn print_value(token: Option<&Token>) -> Option<String> {
+    match token? {
+        Token::Str(value) => Some(quote(value)),
+        Token::Literal(value) => Some(value.clone()),
+        _ => None,
+    }
+}
+
+// Node names and property keys are written bare unless the lexer would read
+// them back as something other than an identifier.
+fn print_identifier(name: &str) -> String {
+    let quoted = name.is_empty()
+        || name.chars().any(is_delimiter)
+        || is_number_like(name)
+        || matches!(name, "true" | "false" | "null")
+        || name.starts_with("r#");
+    if quoted {
+        quote(name)
+    } else {
+        name.to_string()
+    }
+}
+
+fn is_number_like(word: &str) -> bool {
+    let unsigned = word.strip_prefix(['-', '+']).unwrap_or(word);
+    unsigned.starts_with(|c: char| c.is_ascii_digit())
+}
+
+fn quote(value: &str) -> String {
+    let mut out = String::from("\"");
+    for ch in value.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => {
+                out.push_str(&format!("\\u{{{:x}}}", c as u32))
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn print_items(items: &[Item], depth: usize, out: &mut String) {
+    let indent = INDENT.repeat(depth);
+    for item in items {
+        match item {
+            Item::Comment { text, blank_before } => {
+                if *blank_before {
+                    out.push('\n');
+                }
+                out.push_str(&indent);
+                out.push_str(text);
+                out.push('\n');
+            }
+            Item::Node(node) => print_node(node, depth, out),
+        }
+    }
+}
+
+fn print_node(node: &Node, depth: usize, out: &mut String) {
+    let indent = INDENT.repeat(depth);
+    if node.blank_before {
+        out.push('\n');
+    }
+    out.push_str(&indent);
+    if node.slashdash {
+        out.push_str("/-");
+    }
+    out.push_str(&print_identifier(&node.name));
+
+    for entry in ordered_entries(node) {
+        out.push(' ');
+        match entry {
+            Entry::Arg { slashdash, value } => {
+                if *slashdash {
+                    out.push_str("/-");
+                }
+                out.push_str(value);
+            }
+            Entry::Prop {
+                slashdash,
+                key,
+                value,
+            } => {
+                if *slashdash {
+                    out.push_str("/-");
+                }
+                out.push_str(&format!("{}={}", print_identifier(key), value));
+            }
+            Entry::Comment(text) => out.push_str(text),
+        }
+    }
+
+    if let Some(children) = &node.children {
+        if children.items.is_empty() && children.open_comment.is_none() {
+            out.push_str(" {}");
+        } else {
+            out.push_str(" {");
+            if let Some(comment) = &children.open_comment {
+                out.push(' ');
+                out.push_str(comment);
+            }
+            out.push('\n');
+            print_items(&children.items, depth + 1, out);
+            out.push_str(&indent);
+            out.push('}');
+        }
+    }
+
+    if let Some(comment) = &node.trailing_comment {
+        out.push(' ');
+        out.push_str(comment);
+    }
+    out.push('\n');
+}
+
+// Positional values keep their order and come before properties, which are
+// sorted into the node's canonical order. Nodes with inline block comments
+// are left alone, since there is no telling which entry a comment is about.
+fn ordered_entries(node: &Node) -> Vec<&Entry> {
+    let mut entries = node.entries.iter().collect::<Vec<_>>();
+    if entries
+        .iter()
+        .any(|entry| matches!(entry, Entry::Comment(_)))
+    {
+        return entries;
+    }
+
+    let order = PROPERTY_ORDER
+        .iter()
+        .find(|(name, _)| *name == node.name)
+        .map(|(_, order)| *order)
+        .unwrap_or(&[]);
+    entries.sort_by_key(|entry| match entry {
+        Entry::Prop { key, .. } => {
+            1 + order
+                .iter()
+                .position(|name| name == key)
+                .unwrap_or(order.len())
+        }
+        _ => 0,
+    });
+    entries
+}
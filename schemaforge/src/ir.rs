@@ -1,4 +1,11 @@
 pub mod ast;
+pub mod expr;
 pub mod plan;
 pub mod query;
 pub mod schema;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PrintOrder {
+    Sorted,
+    Declaration,
+}
@@ -1,5 +1,8 @@
 mod kdl;
 mod types;
 
-pub use kdl::{parse_kdl, print_kdl};
-pub use types::{AstField, AstParam, AstProc, AstQuery, AstSchema, AstTable};
+pub use kdl::{parse_kdl, print_kdl, print_kdl_with};
+pub use types::{
+    AstCheck, AstEnum, AstField, AstMixin, AstParam, AstProc, AstQuery,
+    AstSchema, AstTable, AstUse,
+};
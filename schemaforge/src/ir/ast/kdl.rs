@@ -1,64 +1,140 @@
 use crate::error::Error;
 use crate::ir::ast::{
-    AstField, AstParam, AstProc, AstQuery, AstSchema, AstTable,
+    AstCheck, AstEnum, AstField, AstMixin, AstParam, AstProc, AstQuery,
+    AstSchema, AstTable, AstUse,
 };
+use crate::ir::expr::{parse_expr, parse_expr_entry, Expr};
+use crate::ir::PrintOrder;
 use kdl::{KdlDocument, KdlEntry, KdlNode, KdlValue};
+use std::collections::HashSet;
 
 pub fn parse_kdl(src: &str) -> Result<AstSchema, Error> {
     let doc: KdlDocument = src.parse()?;
-    let mut tables = Vec::new();
-    let mut procs = Vec::new();
-    let mut queries = Vec::new();
+    let mut schema = AstSchema {
+        includes: Vec::new(),
+        mixins: Vec::new(),
+        enums: Vec::new(),
+        tables: Vec::new(),
+        procs: Vec::new(),
+        queries: Vec::new(),
+        lint_allow: Vec::new(),
+    };
 
     for node in doc.nodes() {
         match node.name().value() {
-            "table" => tables.push(parse_table(node)?),
-            "proc" => procs.push(parse_proc(node)?),
-            "query" => queries.push(parse_query(node)?),
+            "include" => schema.includes.push(parse_include(node)?),
+            "mixin" => schema.mixins.push(parse_mixin(node)?),
+            "enum" => schema.enums.push(parse_enum(node)?),
+            "module" => parse_module(node, &mut schema)?,
+            "table" => schema.tables.push(parse_table(node, None)?),
+            "proc" => schema.procs.push(parse_proc(node, None)?),
+            "query" => schema.queries.push(parse_query(node, None)?),
+            "lint" => schema.lint_allow.extend(parse_lint(node)?),
             other => {
                 return Err(Error::Parse(format!(
-                "unknown root node '{}', expected 'table', 'proc', or 'query'",
+                "unknown root node '{}', expected 'include', 'mixin', 'enum', 'module', 'table', 'proc', 'query', or 'lint'",
                 other
             )))
             }
         }
     }
 
-    Ok(AstSchema {
-        tables,
-        procs,
-        queries,
-    })
+    Ok(schema)
 }
 
 pub fn print_kdl(value: &AstSchema) -> String {
-    let mut tables = value.tables.clone();
-    tables.sort_by(|a, b| a.name.cmp(&b.name));
+    print_kdl_with(value, PrintOrder::Sorted)
+}
 
+pub fn print_kdl_with(value: &AstSchema, order: PrintOrder) -> String {
+    let mut mixins = value.mixins.clone();
+    let mut enums = value.enums.clone();
+    let mut tables = value.tables.clone();
     let mut procs = value.procs.clone();
-    procs.sort_by(|a, b| a.name.cmp(&b.name));
-
     let mut queries = value.queries.clone();
-    queries.sort_by(|a, b| a.name.cmp(&b.name));
+    if order == PrintOrder::Sorted {
+        mixins.sort_by(|a, b| a.name.cmp(&b.name));
+        enums.sort_by(|a, b| a.name.cmp(&b.name));
+        tables.sort_by(|a, b| a.name.cmp(&b.name));
+        procs.sort_by(|a, b| a.name.cmp(&b.name));
+        queries.sort_by(|a, b| a.name.cmp(&b.name));
+    }
 
     let mut out = String::new();
 
+    for include in &value.includes {
+        out.push_str(&format!("include \"{}\"\n", escape(include)));
+    }
+
+    if !value.lint_allow.is_empty() {
+        out.push_str("lint {\n");
+        for rule in &value.lint_allow {
+            out.push_str(&format!("  allow \"{}\"\n", escape(rule)));
+        }
+        out.push_str("}\n");
+    }
+
+    for mixin in mixins {
+        let mut fields = mixin.fields.clone();
+        if order == PrintOrder::Sorted {
+            fields.sort_by(|a, b| a.name.cmp(&b.name));
+        }
+
+        if fields.is_empty() {
+            out.push_str(&format!("mixin \"{}\"\n", escape(&mixin.name)));
+            continue;
+        }
+
+        out.push_str(&format!("mixin \"{}\" {{\n", escape(&mixin.name)));
+        for field in &fields {
+            print_field(&mut out, field);
+        }
+        out.push_str("}\n");
+    }
+
+    for enum_def in enums {
+        out.push_str(&format!(
+            "enum \"{}\" repr=\"{}\"",
+            escape(&enum_def.name),
+            escape(&enum_def.repr)
+        ));
+
+        if enum_def.variants.is_empty() {
+            out.push('\n');
+            continue;
+        }
+
+        out.push_str(" {\n");
+        for variant in &enum_def.variants {
+            out.push_str(&format!("  variant \"{}\"\n", escape(variant)));
+        }
+        out.push_str("}\n");
+    }
+
     for table in tables {
         let mut fields = table.fields.clone();
-        fields.sort_by(|a, b| a.name.cmp(&b.name));
+        let mut uses = table.uses.clone();
+        if order == PrintOrder::Sorted {
+            fields.sort_by(|a, b| a.name.cmp(&b.name));
+            uses.sort_by(|a, b| a.mixin.cmp(&b.mixin));
+            for table_use in &mut uses {
+                table_use.position = 0;
+            }
+        }
 
-        if fields.is_empty() {
+        if fields.is_empty() && uses.is_empty() && table.checks.is_empty() {
             out.push_str(&format!("table \"{}\"\n", escape(&table.name)));
             continue;
         }
 
         out.push_str(&format!("table \"{}\" {{\n", escape(&table.name)));
-        for field in fields {
-            out.push_str(&format!(
-                "  field \"{}\" type=\"{}\"\n",
-                escape(&field.name),
-                escape(&field.ty)
-            ));
+        for (index, field) in fields.iter().enumerate() {
+            print_uses_at(&mut out, &uses, index);
+            print_field(&mut out, field);
+        }
+        print_uses_at(&mut out, &uses, fields.len());
+        for check in &table.checks {
+            print_check(&mut out, check, "  ");
         }
         out.push_str("}\n");
     }
@@ -93,7 +169,7 @@ pub fn print_kdl(value: &AstSchema) -> String {
             escape(&query.table)
         ));
 
-        if query.projection.is_empty() {
+        if query.projection.is_empty() && query.filter.is_none() {
             out.push('\n');
             continue;
         }
@@ -102,22 +178,173 @@ pub fn print_kdl(value: &AstSchema) -> String {
         for column in query.projection {
             out.push_str(&format!("  project \"{}\"\n", escape(&column)));
         }
+        if let Some(filter) = &query.filter {
+            out.push_str(&format!(
+                "  filter \"{}\"\n",
+                escape(&filter.to_string())
+            ));
+        }
         out.push_str("}\n");
     }
 
     out
 }
 
-fn parse_table(node: &KdlNode) -> Result<AstTable, Error> {
-    let name = expect_single_string_value(node, "table")?;
-    ensure_no_properties(node, "table")?;
+struct ModuleScope {
+    name: String,
+    tables: HashSet<String>,
+}
+
+impl ModuleScope {
+    fn qualify(&self, name: &str) -> String {
+        format!("{}.{}", self.name, name)
+    }
+
+    fn qualify_table_ref(&self, name: &str) -> String {
+        if self.tables.contains(name) {
+            self.qualify(name)
+        } else {
+            name.to_string()
+        }
+    }
+}
+
+fn parse_include(node: &KdlNode) -> Result<String, Error> {
+    let path = expect_single_string_value(node, "include")?;
+    ensure_no_properties(node, "include")?;
+    if node.children().is_some() {
+        return Err(Error::Parse(
+            "'include' node does not support children".into(),
+        ));
+    }
+    Ok(path)
+}
+
+fn parse_lint(node: &KdlNode) -> Result<Vec<String>, Error> {
+    ensure_no_properties(node, "lint")?;
+    if node.entries().iter().any(|entry| entry.name().is_none()) {
+        return Err(Error::Parse("'lint' node does not take values".into()));
+    }
+
+    let mut allow = Vec::new();
+    if let Some(children) = node.children() {
+        for child in children.nodes() {
+            if child.name().value() != "allow" {
+                return Err(Error::Parse(format!(
+                    "unknown node '{}' in lint, expected 'allow'",
+                    child.name().value()
+                )));
+            }
+            allow.push(expect_single_string_value(child, "allow")?);
+            ensure_no_properties(child, "allow")?;
+            if child.children().is_some() {
+                return Err(Error::Parse(
+                    "'allow' node in lint does not support children".into(),
+                ));
+            }
+        }
+    }
+    Ok(allow)
+}
+
+fn parse_module(node: &KdlNode, schema: &mut AstSchema) -> Result<(), Error> {
+    let name = expect_single_string_value(node, "module")?;
+    ensure_no_properties(node, "module")?;
+    if name.is_empty() || name.contains('.') {
+        return Err(Error::Parse(format!(
+            "module name '{}' must be non-empty and must not contain '.'",
+            name
+        )));
+    }
+
+    let children = match node.children() {
+        Some(children) => children.nodes(),
+        None => return Ok(()),
+    };
+
+    let mut scope = ModuleScope {
+        name,
+        tables: HashSet::new(),
+    };
+    for child in children {
+        if child.name().value() == "table" {
+            scope
+                .tables
+                .insert(expect_single_string_value(child, "table")?);
+        }
+    }
+
+    for child in children {
+        match child.name().value() {
+            "table" => schema.tables.push(parse_table(child, Some(&scope))?),
+            "proc" => schema.procs.push(parse_proc(child, Some(&scope))?),
+            "query" => schema.queries.push(parse_query(child, Some(&scope))?),
+            other => {
+                return Err(Error::Parse(format!(
+                    "unknown node '{}' in module '{}', expected 'table', 'proc', or 'query'",
+                    other, scope.name
+                )))
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn print_field(out: &mut String, field: &AstField) {
+    out.push_str(&format!(
+        "  field \"{}\" type=\"{}\"",
+        escape(&field.name),
+        escape(&field.ty)
+    ));
+    print_field_properties(
+        out,
+        field.key,
+        field.auto_increment,
+        &field.default,
+        &field.computed,
+    );
+
+    if field.checks.is_empty() {
+        out.push('\n');
+        return;
+    }
+
+    out.push_str(" {\n");
+    for check in &field.checks {
+        print_check(out, check, "    ");
+    }
+    out.push_str("  }\n");
+}
+
+fn print_check(out: &mut String, check: &AstCheck, indent: &str) {
+    out.push_str(&format!(
+        "{}check \"{}\"",
+        indent,
+        escape(&check.expr.to_string())
+    ));
+    if let Some(name) = &check.name {
+        out.push_str(&format!(" name=\"{}\"", escape(name)));
+    }
+    out.push('\n');
+}
+
+fn print_uses_at(out: &mut String, uses: &[AstUse], position: usize) {
+    for table_use in uses.iter().filter(|value| value.position == position) {
+        out.push_str(&format!("  use \"{}\"\n", escape(&table_use.mixin)));
+    }
+}
+
+fn parse_mixin(node: &KdlNode) -> Result<AstMixin, Error> {
+    let name = expect_single_string_value(node, "mixin")?;
+    ensure_no_properties(node, "mixin")?;
 
     let mut fields = Vec::new();
     if let Some(children) = node.children() {
         for child in children.nodes() {
             if child.name().value() != "field" {
                 return Err(Error::Parse(format!(
-                    "unknown node '{}' in table '{}', expected 'field'",
+                    "unknown node '{}' in mixin '{}', expected 'field'",
                     child.name().value(),
                     name
                 )));
@@ -126,21 +353,165 @@ fn parse_table(node: &KdlNode) -> Result<AstTable, Error> {
         }
     }
 
-    Ok(AstTable { name, fields })
+    Ok(AstMixin {
+        name,
+        fields,
+        source: None,
+    })
+}
+
+fn parse_enum(node: &KdlNode) -> Result<AstEnum, Error> {
+    let name = expect_single_string_value(node, "enum")?;
+    ensure_only_properties(node, "enum", &["repr"], "")?;
+    let repr = optional_string_property(node, "repr")?
+        .unwrap_or_else(|| "text".to_string());
+
+    let mut variants = Vec::new();
+    if let Some(children) = node.children() {
+        for child in children.nodes() {
+            if child.name().value() != "variant" {
+                return Err(Error::Parse(format!(
+                    "unknown node '{}' in enum '{}', expected 'variant'",
+                    child.name().value(),
+                    name
+                )));
+            }
+            variants.push(expect_single_string_value(child, "variant")?);
+            ensure_no_properties(child, "variant")?;
+            if child.children().is_some() {
+                return Err(Error::Parse(format!(
+                    "'variant' node in enum '{}' does not support children",
+                    name
+                )));
+            }
+        }
+    }
+
+    Ok(AstEnum {
+        name,
+        repr,
+        variants,
+        source: None,
+    })
+}
+
+fn parse_use(node: &KdlNode, table_name: &str) -> Result<String, Error> {
+    let mixin = expect_single_string_value(node, "use")?;
+    ensure_no_properties(node, "use")?;
+    if node.children().is_some() {
+        return Err(Error::Parse(format!(
+            "'use' node in table '{}' does not support children",
+            table_name
+        )));
+    }
+    Ok(mixin)
+}
+
+fn parse_table(
+    node: &KdlNode,
+    module: Option<&ModuleScope>,
+) -> Result<AstTable, Error> {
+    let name = expect_single_string_value(node, "table")?;
+    ensure_no_properties(node, "table")?;
+    let name = match module {
+        Some(module) => module.qualify(&name),
+        None => name,
+    };
+
+    let mut fields = Vec::new();
+    let mut uses = Vec::new();
+    let mut checks = Vec::new();
+    if let Some(children) = node.children() {
+        for child in children.nodes() {
+            match child.name().value() {
+                "field" => fields.push(parse_field(child, &name)?),
+                "use" => uses.push(AstUse {
+                    mixin: parse_use(child, &name)?,
+                    position: fields.len(),
+                }),
+                "check" => checks.push(parse_check(child, &name)?),
+                other => {
+                    return Err(Error::Parse(format!(
+                        "unknown node '{}' in table '{}', expected 'field', 'use', or 'check'",
+                        other, name
+                    )))
+                }
+            }
+        }
+    }
+
+    Ok(AstTable {
+        name,
+        fields,
+        uses,
+        checks,
+        source: None,
+    })
 }
 
 fn parse_field(node: &KdlNode, table_name: &str) -> Result<AstField, Error> {
     let name = expect_single_string_value(node, "field")?;
     let ty = expect_string_property(node, "type")?;
-    ensure_only_properties(node, "field", &["type"], table_name)?;
+    ensure_only_properties(
+        node,
+        "field",
+        &["type", "key", "auto-increment", "default", "computed"],
+        table_name,
+    )?;
+    let key = optional_bool_property(node, "key")?;
+    let auto_increment = optional_bool_property(node, "auto-increment")?;
+    let default = optional_literal_property(node, "default")?;
+    let computed = optional_string_property(node, "computed")?
+        .map(|expr| parse_expr(&expr))
+        .transpose()?;
+
+    let mut checks = Vec::new();
+    if let Some(children) = node.children() {
+        for child in children.nodes() {
+            if child.name().value() != "check" {
+                return Err(Error::Parse(format!(
+                    "unknown node '{}' in field '{}', expected 'check'",
+                    child.name().value(),
+                    name
+                )));
+            }
+            checks.push(parse_check(child, &name)?);
+        }
+    }
 
-    Ok(AstField { name, ty })
+    Ok(AstField {
+        name,
+        ty,
+        key,
+        auto_increment,
+        default,
+        computed,
+        checks,
+    })
 }
 
-fn parse_proc(node: &KdlNode) -> Result<AstProc, Error> {
+fn parse_check(node: &KdlNode, owner: &str) -> Result<AstCheck, Error> {
+    ensure_only_properties(node, "check", &["name"], owner)?;
+
+    Ok(AstCheck {
+        name: optional_string_property(node, "name")?,
+        expr: parse_expr_entry(node, "check")?,
+    })
+}
+
+fn parse_proc(
+    node: &KdlNode,
+    module: Option<&ModuleScope>,
+) -> Result<AstProc, Error> {
     let name = expect_single_string_value(node, "proc")?;
     let table = expect_string_property(node, "table")?;
     ensure_only_properties(node, "proc", &["table"], "")?;
+    let (name, table) = match module {
+        Some(module) => {
+            (module.qualify(&name), module.qualify_table_ref(&table))
+        }
+        None => (name, table),
+    };
 
     let mut params = Vec::new();
     if let Some(children) = node.children() {
@@ -160,6 +531,7 @@ fn parse_proc(node: &KdlNode) -> Result<AstProc, Error> {
         name,
         table,
         params,
+        source: None,
     })
 }
 
@@ -171,20 +543,36 @@ fn parse_param(node: &KdlNode, proc_name: &str) -> Result<AstParam, Error> {
     Ok(AstParam { name, ty })
 }
 
-fn parse_query(node: &KdlNode) -> Result<AstQuery, Error> {
+fn parse_query(
+    node: &KdlNode,
+    module: Option<&ModuleScope>,
+) -> Result<AstQuery, Error> {
     let name = expect_single_string_value(node, "query")?;
     let table = expect_string_property(node, "table")?;
     ensure_only_properties(node, "query", &["table"], "")?;
+    let (name, table) = match module {
+        Some(module) => {
+            (module.qualify(&name), module.qualify_table_ref(&table))
+        }
+        None => (name, table),
+    };
 
     let mut projection = Vec::new();
+    let mut filter = None;
     if let Some(children) = node.children() {
         for child in children.nodes() {
-            if child.name().value() != "project" {
-                return Err(Error::Parse(format!(
-                    "unknown node '{}' in query '{}', expected 'project'",
-                    child.name().value(),
-                    name
-                )));
+            match child.name().value() {
+                "project" => {}
+                "filter" => {
+                    filter = Some(parse_filter(child, &name, filter)?);
+                    continue;
+                }
+                other => {
+                    return Err(Error::Parse(format!(
+                        "unknown node '{}' in query '{}', expected 'project' or 'filter'",
+                        other, name
+                    )))
+                }
             }
             projection.push(expect_single_string_value(child, "project")?);
             ensure_no_properties(child, "project")?;
@@ -201,9 +589,26 @@ fn parse_query(node: &KdlNode) -> Result<AstQuery, Error> {
         name,
         table,
         projection,
+        filter,
+        source: None,
     })
 }
 
+fn parse_filter(
+    node: &KdlNode,
+    query_name: &str,
+    previous: Option<Expr>,
+) -> Result<Expr, Error> {
+    if previous.is_some() {
+        return Err(Error::Parse(format!(
+            "query '{}' declares more than one filter",
+            query_name
+        )));
+    }
+    ensure_no_properties(node, "filter")?;
+    parse_expr_entry(node, "filter")
+}
+
 fn expect_single_string_value(
     node: &KdlNode,
     kind: &str,
@@ -248,6 +653,96 @@ fn expect_string_property(node: &KdlNode, key: &str) -> Result<String, Error> {
     }
 }
 
+fn optional_string_property(
+    node: &KdlNode,
+    key: &str,
+) -> Result<Option<String>, Error> {
+    let entry = node.entries().iter().find(|entry| {
+        entry
+            .name()
+            .map(|name| name.value() == key)
+            .unwrap_or(false)
+    });
+
+    match entry.map(|entry| entry.value()) {
+        None => Ok(None),
+        Some(KdlValue::String(s)) => Ok(Some(s.to_string())),
+        Some(_) => {
+            Err(Error::Parse(format!("property '{}' must be a string", key)))
+        }
+    }
+}
+
+fn optional_bool_property(node: &KdlNode, key: &str) -> Result<bool, Error> {
+    let entry = node.entries().iter().find(|entry| {
+        entry
+            .name()
+            .map(|name| name.value() == key)
+            .unwrap_or(false)
+    });
+
+    match entry.map(|entry| entry.value()) {
+        None => Ok(false),
+        Some(KdlValue::Bool(value)) => Ok(*value),
+        Some(_) => Err(Error::Parse(format!(
+            "property '{}' must be a boolean",
+            key
+        ))),
+    }
+}
+
+fn optional_literal_property(
+    node: &KdlNode,
+    key: &str,
+) -> Result<Option<Expr>, Error> {
+    let entry = node.entries().iter().find(|entry| {
+        entry
+            .name()
+            .map(|name| name.value() == key)
+            .unwrap_or(false)
+    });
+
+    match entry.map(|entry| entry.value()) {
+        None => Ok(None),
+        Some(KdlValue::String(s)) => Ok(Some(Expr::Text(s.to_string()))),
+        Some(KdlValue::Base10(value)) => Ok(Some(Expr::Integer(*value))),
+        Some(KdlValue::Bool(value)) => Ok(Some(Expr::Bool(*value))),
+        Some(KdlValue::Null) => Ok(Some(Expr::Null)),
+        Some(_) => Err(Error::Parse(format!(
+            "property '{}' must be a string, integer, boolean, or null",
+            key
+        ))),
+    }
+}
+
+fn print_literal(value: &Expr) -> String {
+    match value {
+        Expr::Text(s) => format!("\"{}\"", escape(s)),
+        other => other.to_string(),
+    }
+}
+
+fn print_field_properties(
+    out: &mut String,
+    key: bool,
+    auto_increment: bool,
+    default: &Option<Expr>,
+    computed: &Option<Expr>,
+) {
+    if key {
+        out.push_str(" key=true");
+    }
+    if auto_increment {
+        out.push_str(" auto-increment=true");
+    }
+    if let Some(value) = default {
+        out.push_str(&format!(" default={}", print_literal(value)));
+    }
+    if let Some(expr) = computed {
+        out.push_str(&format!(" computed=\"{}\"", escape(&expr.to_string())));
+    }
+}
+
 fn ensure_no_properties(node: &KdlNode, kind: &str) -> Result<(), Error> {
     for entry in node.entries() {
         if let Some(name) = entry.name() {
@@ -1,20 +1,61 @@
+use crate::ir::expr::Expr;
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct AstSchema {
+    pub includes: Vec<String>,
+    pub mixins: Vec<AstMixin>,
+    pub enums: Vec<AstEnum>,
     pub tables: Vec<AstTable>,
     pub procs: Vec<AstProc>,
     pub queries: Vec<AstQuery>,
+    pub lint_allow: Vec<String>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct AstTable {
     pub name: String,
     pub fields: Vec<AstField>,
+    pub uses: Vec<AstUse>,
+    pub checks: Vec<AstCheck>,
+    pub source: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AstUse {
+    pub mixin: String,
+    pub position: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AstMixin {
+    pub name: String,
+    pub fields: Vec<AstField>,
+    pub source: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AstEnum {
+    pub name: String,
+    pub repr: String,
+    pub variants: Vec<String>,
+    pub source: Option<String>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct AstField {
     pub name: String,
     pub ty: String,
+    pub key: bool,
+    pub auto_increment: bool,
+    pub default: Option<Expr>,
+    pub computed: Option<Expr>,
+    pub checks: Vec<AstCheck>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AstCheck {
+    pub name: Option<String>,
+    pub expr: Expr,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -22,6 +63,7 @@ pub struct AstProc {
     pub name: String,
     pub table: String,
     pub params: Vec<AstParam>,
+    pub source: Option<String>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -35,4 +77,6 @@ pub struct AstQuery {
     pub name: String,
     pub table: String,
     pub projection: Vec<String>,
+    pub filter: Option<Expr>,
+    pub source: Option<String>,
 }
@@ -0,0 +1,12 @@
+mod kdl;
+mod parse;
+mod rust;
+mod typecheck;
+mod types;
+
+pub use kdl::{parse_expr_entry, parse_expr_node};
+pub use parse::parse_expr;
+pub use rust::print_rust;
+pub(crate) use typecheck::enum_comparison;
+pub use typecheck::{check_predicate, infer_type, BOOL_TYPE, NULL_TYPE};
+pub use types::{BinaryOp, Expr, UnaryOp, FUNCTIONS};
@@ -0,0 +1,209 @@
+use crate::error::Error;
+use crate::ir::expr::{parse_expr, BinaryOp, Expr, UnaryOp};
+use kdl::{KdlEntry, KdlNode, KdlValue};
+
+const BINARY_NODES: [(&str, BinaryOp); 14] = [
+    ("or", BinaryOp::Or),
+    ("and", BinaryOp::And),
+    ("eq", BinaryOp::Eq),
+    ("ne", BinaryOp::Ne),
+    ("lt", BinaryOp::Lt),
+    ("le", BinaryOp::Le),
+    ("gt", BinaryOp::Gt),
+    ("ge", BinaryOp::Ge),
+    ("add", BinaryOp::Add),
+    ("sub", BinaryOp::Sub),
+    ("concat", BinaryOp::Concat),
+    ("mul", BinaryOp::Mul),
+    ("div", BinaryOp::Div),
+    ("rem", BinaryOp::Rem),
+];
+
+// Parses the expression attached to a `check`, `filter` or similar node,
+// given either as a single string value (`check "age >= 0"`) or as a single
+// nested expression node (`check { ge { column "age"; value 0 } }`).
+pub fn parse_expr_entry(node: &KdlNode, kind: &str) -> Result<Expr, Error> {
+    let values = positional_values(node);
+    let children = node.children().map(|children| children.nodes());
+
+    match (values.as_slice(), children.unwrap_or(&[])) {
+        ([value], []) => match value.value() {
+            KdlValue::String(src) => parse_expr(src),
+            _ => Err(Error::Parse(format!(
+                "'{}' node expression must be a string",
+                kind
+            ))),
+        },
+        ([], [child]) => parse_expr_node(child),
+        _ => Err(Error::Parse(format!(
+            "'{}' node must have either one string expression or one nested expression node",
+            kind
+        ))),
+    }
+}
+
+pub fn parse_expr_node(node: &KdlNode) -> Result<Expr, Error> {
+    let kind = node.name().value();
+    if let Some(entry) = node.entries().iter().find(|e| e.name().is_some()) {
+        return Err(Error::Parse(format!(
+            "'{}' expression node does not support property '{}'",
+            kind,
+            entry.name().map(|name| name.value()).unwrap_or_default()
+        )));
+    }
+
+    match kind {
+        "column" => {
+            let value = single_value(node, kind)?;
+            expect_no_operands(node, kind)?;
+            match value {
+                KdlValue::String(name) => Ok(Expr::Column(name.to_string())),
+                _ => Err(Error::Parse(
+                    "'column' expression node must name a column with a string"
+                        .into(),
+                )),
+            }
+        }
+        "value" => {
+            let value = single_value(node, kind)?;
+            expect_no_operands(node, kind)?;
+            match value {
+                KdlValue::String(value) => Ok(Expr::Text(value.to_string())),
+                // A negative literal parses like `-1` in a string
+                // expression, so both spellings give the same IR.
+                KdlValue::Base10(value) => Ok(Expr::Integer(*value).canonical()),
+                KdlValue::Bool(value) => Ok(Expr::Bool(*value)),
+                KdlValue::Null => Ok(Expr::Null),
+                _ => Err(Error::Parse(
+                    "'value' expression node must be a string, integer, boolean, or null"
+                        .into(),
+                )),
+            }
+        }
+        "not" | "neg" => {
+            expect_no_values(node, kind)?;
+            let [operand] = operands::<1>(node, kind)?;
+            Ok(Expr::Unary {
+                op: if kind == "not" {
+                    UnaryOp::Not
+                } else {
+                    UnaryOp::Neg
+                },
+                operand: Box::new(operand),
+            })
+        }
+        "is-null" | "is-not-null" => {
+            expect_no_values(node, kind)?;
+            let [operand] = operands::<1>(node, kind)?;
+            Ok(Expr::IsNull {
+                operand: Box::new(operand),
+                negated: kind == "is-not-null",
+            })
+        }
+        "call" => {
+            let function = match single_value(node, kind)? {
+                KdlValue::String(name) => name.to_string(),
+                _ => return Err(Error::Parse(
+                    "'call' expression node must name a function with a string"
+                        .into(),
+                )),
+            };
+            let args = child_exprs(node)?;
+            let expr = Expr::Call { function, args };
+            expr.check_calls().map_err(|message| {
+                Error::Parse(format!("invalid expression: {}", message))
+            })?;
+            Ok(expr)
+        }
+        other => {
+            let op = BINARY_NODES
+                .iter()
+                .find(|(name, _)| *name == other)
+                .map(|(_, op)| *op)
+                .ok_or_else(|| {
+                    Error::Parse(format!("unknown expression node '{}'", other))
+                })?;
+            expect_no_values(node, kind)?;
+            let operands = child_exprs(node)?;
+            let variadic = matches!(op, BinaryOp::Or | BinaryOp::And);
+            if operands.len() < 2 || (!variadic && operands.len() != 2) {
+                return Err(Error::Parse(format!(
+                    "'{}' expression node expects {} operands, got {}",
+                    kind,
+                    if variadic { "at least 2" } else { "2" },
+                    operands.len()
+                )));
+            }
+            let mut operands = operands.into_iter();
+            let first = operands.next().expect("at least two operands");
+            Ok(operands.fold(first, |left, right| Expr::Binary {
+                op,
+                left: Box::new(left),
+                right: Box::new(right),
+            }))
+        }
+    }
+}
+
+fn positional_values(node: &KdlNode) -> Vec<&KdlEntry> {
+    node.entries()
+        .iter()
+        .filter(|entry| entry.name().is_none())
+        .collect()
+}
+
+fn single_value<'a>(
+    node: &'a KdlNode,
+    kind: &str,
+) -> Result<&'a KdlValue, Error> {
+    match positional_values(node).as_slice() {
+        [entry] => Ok(entry.value()),
+        _ => Err(Error::Parse(format!(
+            "'{}' expression node must have exactly one value",
+            kind
+        ))),
+    }
+}
+
+fn expect_no_values(node: &KdlNode, kind: &str) -> Result<(), Error> {
+    if positional_values(node).is_empty() {
+        return Ok(());
+    }
+    Err(Error::Parse(format!(
+        "'{}' expression node does not take values, only nested operands",
+        kind
+    )))
+}
+
+fn expect_no_operands(node: &KdlNode, kind: &str) -> Result<(), Error> {
+    if node.children().is_none() {
+        return Ok(());
+    }
+    Err(Error::Parse(format!(
+        "'{}' expression node does not support children",
+        kind
+    )))
+}
+
+fn child_exprs(node: &KdlNode) -> Result<Vec<Expr>, Error> {
+    match node.children() {
+        Some(children) => {
+            children.nodes().iter().map(parse_expr_node).collect()
+        }
+        None => Ok(Vec::new()),
+    }
+}
+
+fn operands<const N: usize>(
+    node: &KdlNode,
+    kind: &str,
+) -> Result<[Expr; N], Error> {
+    child_exprs(node)?.try_into().map_err(|found: Vec<Expr>| {
+        Error::Parse(format!(
+            "'{}' expression node expects {} operand(s), got {}",
+            kind,
+            N,
+            found.len()
+        ))
+    })
+}
@@ -0,0 +1,331 @@
+use crate::error::Error;
+use crate::ir::expr::{BinaryOp, Expr, UnaryOp};
+
+pub fn parse_expr(src: &str) -> Result<Expr, Error> {
+    let tokens = tokenize(src)?;
+    let mut parser = Parser {
+        src,
+        tokens,
+        pos: 0,
+    };
+    let expr = parser.parse_or()?;
+    if let Some(token) = parser.peek() {
+        return Err(parser.error(&format!("unexpected {}", token.describe())));
+    }
+    Ok(expr)
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Token {
+    Ident(String),
+    // Unsigned so that `-9223372036854775808` can be read as `i64::MIN`.
+    Integer(u64),
+    Text(String),
+    Symbol(&'static str),
+}
+
+impl Token {
+    fn describe(&self) -> String {
+        match self {
+            Token::Ident(name) => format!("'{}'", name),
+            Token::Integer(value) => format!("'{}'", value),
+            Token::Text(value) => format!("string '{}'", value),
+            Token::Symbol(symbol) => format!("'{}'", symbol),
+        }
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self, Token::Ident(name) if name.eq_ignore_ascii_case(keyword))
+    }
+}
+
+const SYMBOLS: [&str; 16] = [
+    "<>", "!=", "==", "<=", ">=", "||", "<", ">", "=", "+", "-", "*", "/", "%",
+    "(", ")",
+];
+
+fn tokenize(src: &str) -> Result<Vec<Token>, Error> {
+    let mut tokens = Vec::new();
+    let mut chars = src.char_indices().peekable();
+
+    while let Some(&(start, ch)) = chars.peek() {
+        if ch.is_whitespace() {
+            chars.next();
+        } else if ch == ',' {
+            chars.next();
+            tokens.push(Token::Symbol(","));
+        } else if ch.is_ascii_digit() {
+            let mut end = start;
+            while let Some(&(index, digit)) = chars.peek() {
+                if !digit.is_ascii_digit() && digit != '_' {
+                    break;
+                }
+                end = index + digit.len_utf8();
+                chars.next();
+            }
+            let text = src[start..end].replace('_', "");
+            let value = text.parse::<u64>().map_err(|_| {
+                expr_error(src, &format!("integer '{}' is out of range", text))
+            })?;
+            tokens.push(Token::Integer(value));
+        } else if ch.is_alphabetic() || ch == '_' {
+            let mut end = start;
+            while let Some(&(index, next)) = chars.peek() {
+                if !next.is_alphanumeric() && next != '_' {
+                    break;
+                }
+                end = index + next.len_utf8();
+                chars.next();
+            }
+            tokens.push(Token::Ident(src[start..end].to_string()));
+        } else if ch == '\'' {
+            chars.next();
+            let mut value = String::new();
+            loop {
+                match chars.next() {
+                    Some((_, '\'')) => {
+                        if matches!(chars.peek(), Some((_, '\''))) {
+                            chars.next();
+                            value.push('\'');
+                        } else {
+                            break;
+                        }
+                    }
+                    Some((_, next)) => value.push(next),
+                    None => return Err(expr_error(src, "unterminated string")),
+                }
+            }
+            tokens.push(Token::Text(value));
+        } else {
+            let rest = &src[start..];
+            let symbol = SYMBOLS
+                .iter()
+                .find(|symbol| rest.starts_with(**symbol))
+                .ok_or_else(|| {
+                    expr_error(src, &format!("unexpected character '{}'", ch))
+                })?;
+            for _ in 0..symbol.len() {
+                chars.next();
+            }
+            tokens.push(Token::Symbol(symbol));
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    src: &'a str,
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn eat_symbol(&mut self, symbol: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Symbol(found)) if *found == symbol)
+        {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        if self.peek().map(|t| t.is_keyword(keyword)).unwrap_or(false) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn error(&self, message: &str) -> Error {
+        expr_error(self.src, message)
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, Error> {
+        let mut left = self.parse_and()?;
+        while self.eat_keyword("or") {
+            let right = self.parse_and()?;
+            left = binary(BinaryOp::Or, left, right);
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, Error> {
+        let mut left = self.parse_not()?;
+        while self.eat_keyword("and") {
+            let right = self.parse_not()?;
+            left = binary(BinaryOp::And, left, right);
+        }
+        Ok(left)
+    }
+
+    fn parse_not(&mut self) -> Result<Expr, Error> {
+        if self.eat_keyword("not") {
+            let operand = self.parse_not()?;
+            return Ok(Expr::Unary {
+                op: UnaryOp::Not,
+                operand: Box::new(operand),
+            });
+        }
+        self.parse_comparison()
+    }
+
+    fn parse_comparison(&mut self) -> Result<Expr, Error> {
+        let mut left = self.parse_additive()?;
+        loop {
+            if self.eat_keyword("is") {
+                let negated = self.eat_keyword("not");
+                if !self.eat_keyword("null") {
+                    return Err(self.error("expected 'null' after 'is'"));
+                }
+                left = Expr::IsNull {
+                    operand: Box::new(left),
+                    negated,
+                };
+                continue;
+            }
+
+            let op = match self.peek() {
+                Some(Token::Symbol("=")) | Some(Token::Symbol("==")) => {
+                    BinaryOp::Eq
+                }
+                Some(Token::Symbol("!=")) | Some(Token::Symbol("<>")) => {
+                    BinaryOp::Ne
+                }
+                Some(Token::Symbol("<")) => BinaryOp::Lt,
+                Some(Token::Symbol("<=")) => BinaryOp::Le,
+                Some(Token::Symbol(">")) => BinaryOp::Gt,
+                Some(Token::Symbol(">=")) => BinaryOp::Ge,
+                _ => return Ok(left),
+            };
+            self.pos += 1;
+            let right = self.parse_additive()?;
+            left = binary(op, left, right);
+        }
+    }
+
+    fn parse_additive(&mut self) -> Result<Expr, Error> {
+        let mut left = self.parse_multiplicative()?;
+        loop {
+            let op = match self.peek() {
+                Some(Token::Symbol("+")) => BinaryOp::Add,
+                Some(Token::Symbol("-")) => BinaryOp::Sub,
+                Some(Token::Symbol("||")) => BinaryOp::Concat,
+                _ => return Ok(left),
+            };
+            self.pos += 1;
+            let right = self.parse_multiplicative()?;
+            left = binary(op, left, right);
+        }
+    }
+
+    fn parse_multiplicative(&mut self) -> Result<Expr, Error> {
+        let mut left = self.parse_unary()?;
+        loop {
+            let op = match self.peek() {
+                Some(Token::Symbol("*")) => BinaryOp::Mul,
+                Some(Token::Symbol("/")) => BinaryOp::Div,
+                Some(Token::Symbol("%")) => BinaryOp::Rem,
+                _ => return Ok(left),
+            };
+            self.pos += 1;
+            let right = self.parse_unary()?;
+            left = binary(op, left, right);
+        }
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, Error> {
+        if self.eat_symbol("-") {
+            if let Some(Token::Integer(value)) = self.peek() {
+                if *value == i64::MIN.unsigned_abs() {
+                    self.pos += 1;
+                    return Ok(Expr::Integer(i64::MIN));
+                }
+            }
+            let operand = self.parse_unary()?;
+            return Ok(Expr::Unary {
+                op: UnaryOp::Neg,
+                operand: Box::new(operand),
+            });
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr, Error> {
+        match self.next() {
+            Some(Token::Integer(value)) => {
+                i64::try_from(value).map(Expr::Integer).map_err(|_| {
+                    self.error(&format!("integer '{}' is out of range", value))
+                })
+            }
+            Some(Token::Text(value)) => Ok(Expr::Text(value)),
+            Some(Token::Symbol("(")) => {
+                let expr = self.parse_or()?;
+                if !self.eat_symbol(")") {
+                    return Err(self.error("expected ')'"));
+                }
+                Ok(expr)
+            }
+            Some(Token::Ident(name)) => {
+                if name.eq_ignore_ascii_case("true") {
+                    return Ok(Expr::Bool(true));
+                }
+                if name.eq_ignore_ascii_case("false") {
+                    return Ok(Expr::Bool(false));
+                }
+                if name.eq_ignore_ascii_case("null") {
+                    return Ok(Expr::Null);
+                }
+                if !self.eat_symbol("(") {
+                    return Ok(Expr::Column(name));
+                }
+
+                let mut args = Vec::new();
+                if !self.eat_symbol(")") {
+                    loop {
+                        args.push(self.parse_or()?);
+                        if self.eat_symbol(")") {
+                            break;
+                        }
+                        if !self.eat_symbol(",") {
+                            return Err(self.error("expected ',' or ')'"));
+                        }
+                    }
+                }
+                Ok(Expr::Call {
+                    function: name,
+                    args,
+                })
+            }
+            Some(token) => {
+                Err(self.error(&format!("unexpected {}", token.describe())))
+            }
+            None => Err(self.error("unexpected end of expression")),
+        }
+    }
+}
+
+fn binary(op: BinaryOp, left: Expr, right: Expr) -> Expr {
+    Expr::Binary {
+        op,
+        left: Box::new(left),
+        right: Box::new(right),
+    }
+}
+
+fn expr_error(src: &str, message: &str) -> Error {
+    Error::Parse(format!("invalid expression '{}': {}", src, message))
+}
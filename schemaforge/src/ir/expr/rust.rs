@@ -0,0 +1,75 @@
+use crate::ir::expr::{BinaryOp, Expr, UnaryOp};
+
+// Prints the expression as Rust source, using `column` to render each
+// column reference. Text values are rendered as `&str` literals and the
+// concatenation operator as `format!`, so text columns may be either
+// `String` or `&str`; nullable values are expected to be `Option`s.
+pub fn print_rust(expr: &Expr, column: &dyn Fn(&str) -> String) -> String {
+    match expr {
+        Expr::Column(name) => column(name),
+        Expr::Integer(value) => format!("{}i64", value),
+        Expr::Text(value) => format!("{:?}", value),
+        Expr::Bool(value) => value.to_string(),
+        Expr::Null => "None".to_string(),
+        Expr::Unary { op, operand } => {
+            let operand = print_operand(operand, column);
+            match op {
+                UnaryOp::Neg => format!("-{}", operand),
+                UnaryOp::Not => format!("!{}", operand),
+            }
+        }
+        Expr::Binary {
+            op: BinaryOp::Concat,
+            left,
+            right,
+        } => format!(
+            "format!(\"{{}}{{}}\", {}, {})",
+            print_rust(left, column),
+            print_rust(right, column)
+        ),
+        Expr::Binary { op, left, right } => {
+            let op = match op {
+                BinaryOp::Or => "||",
+                BinaryOp::And => "&&",
+                BinaryOp::Eq => "==",
+                other => other.symbol(),
+            };
+            format!(
+                "{} {} {}",
+                print_operand(left, column),
+                op,
+                print_operand(right, column)
+            )
+        }
+        Expr::IsNull { operand, negated } => format!(
+            "{}.{}()",
+            print_operand(operand, column),
+            if *negated { "is_some" } else { "is_none" }
+        ),
+        Expr::Call { function, args } => {
+            let arg = args
+                .first()
+                .map(|arg| print_operand(arg, column))
+                .unwrap_or_default();
+            match function.as_str() {
+                "len" => format!("({}.chars().count() as i64)", arg),
+                "lower" => format!("{}.to_lowercase()", arg),
+                "upper" => format!("{}.to_uppercase()", arg),
+                "abs" => format!("{}.abs()", arg),
+                other => format!("{}({})", other, arg),
+            }
+        }
+    }
+}
+
+fn print_operand(expr: &Expr, column: &dyn Fn(&str) -> String) -> String {
+    match expr {
+        Expr::Unary { .. } | Expr::Binary { .. } => {
+            format!("({})", print_rust(expr, column))
+        }
+        Expr::Integer(value) if *value < 0 => {
+            format!("({})", print_rust(expr, column))
+        }
+        _ => print_rust(expr, column),
+    }
+}
@@ -0,0 +1,191 @@
+use crate::ir::expr::{BinaryOp, Expr, UnaryOp};
+use crate::ir::schema::EnumIr;
+
+pub const BOOL_TYPE: &str = "bool";
+pub const NULL_TYPE: &str = "null";
+
+// Infers the type of an expression, using `column_type` to look up the
+// declared type of each referenced column. Enum-typed values may be
+// compared against text, but every other operand must match exactly.
+pub fn infer_type(
+    expr: &Expr,
+    column_type: &dyn Fn(&str) -> Option<String>,
+) -> Result<String, String> {
+    match expr {
+        Expr::Column(name) => column_type(name)
+            .ok_or_else(|| format!("unknown column '{}'", name)),
+        Expr::Integer(_) => Ok("i64".to_string()),
+        Expr::Text(_) => Ok("text".to_string()),
+        Expr::Bool(_) => Ok(BOOL_TYPE.to_string()),
+        Expr::Null => Ok(NULL_TYPE.to_string()),
+        Expr::Unary { op, operand } => {
+            let operand_ty = infer_type(operand, column_type)?;
+            let expected = match op {
+                UnaryOp::Neg => "i64",
+                UnaryOp::Not => BOOL_TYPE,
+            };
+            expect_type(
+                &operand_ty,
+                expected,
+                &format!("operand of '{}'", expr),
+            )?;
+            Ok(expected.to_string())
+        }
+        Expr::Binary { op, left, right } => {
+            let left_ty = infer_type(left, column_type)?;
+            let right_ty = infer_type(right, column_type)?;
+            match op {
+                BinaryOp::Or | BinaryOp::And => {
+                    expect_type(&left_ty, BOOL_TYPE, &operand_desc(op))?;
+                    expect_type(&right_ty, BOOL_TYPE, &operand_desc(op))?;
+                    Ok(BOOL_TYPE.to_string())
+                }
+                BinaryOp::Eq
+                | BinaryOp::Ne
+                | BinaryOp::Lt
+                | BinaryOp::Le
+                | BinaryOp::Gt
+                | BinaryOp::Ge => {
+                    if !comparable(&left_ty, &right_ty) {
+                        return Err(format!(
+                            "cannot compare '{}' with '{}' in '{}'",
+                            left_ty, right_ty, expr
+                        ));
+                    }
+                    Ok(BOOL_TYPE.to_string())
+                }
+                BinaryOp::Concat => {
+                    expect_type(&left_ty, "text", &operand_desc(op))?;
+                    expect_type(&right_ty, "text", &operand_desc(op))?;
+                    Ok("text".to_string())
+                }
+                BinaryOp::Add
+                | BinaryOp::Sub
+                | BinaryOp::Mul
+                | BinaryOp::Div
+                | BinaryOp::Rem => {
+                    expect_type(&left_ty, "i64", &operand_desc(op))?;
+                    expect_type(&right_ty, "i64", &operand_desc(op))?;
+                    Ok("i64".to_string())
+                }
+            }
+        }
+        Expr::IsNull { operand, .. } => {
+            infer_type(operand, column_type)?;
+            Ok(BOOL_TYPE.to_string())
+        }
+        Expr::Call { function, args } => {
+            expr.check_calls()?;
+            let (arg_ty, result_ty) = match function.as_str() {
+                "len" => ("text", "i64"),
+                "lower" | "upper" => ("text", "text"),
+                "abs" => ("i64", "i64"),
+                other => return Err(format!("unknown function '{}'", other)),
+            };
+            for arg in args {
+                let ty = infer_type(arg, column_type)?;
+                expect_type(
+                    &ty,
+                    arg_ty,
+                    &format!("argument of '{}'", function),
+                )?;
+            }
+            Ok(result_ty.to_string())
+        }
+    }
+}
+
+fn operand_desc(op: &BinaryOp) -> String {
+    format!("operand of '{}'", op.symbol())
+}
+
+fn expect_type(found: &str, expected: &str, what: &str) -> Result<(), String> {
+    if found == expected || found == NULL_TYPE {
+        return Ok(());
+    }
+    Err(format!(
+        "{} must have type '{}', found '{}'",
+        what, expected, found
+    ))
+}
+
+fn comparable(left: &str, right: &str) -> bool {
+    left == right
+        || left == NULL_TYPE
+        || right == NULL_TYPE
+        || (is_enum(left) && right == "text")
+        || (left == "text" && is_enum(right))
+}
+
+fn is_enum(ty: &str) -> bool {
+    !matches!(ty, "i64" | "text" | BOOL_TYPE | NULL_TYPE)
+}
+
+// Checks that `expr` is a boolean predicate, as required for checks and
+// query filters, and that text literals compared against enum-typed
+// columns name one of the enum's variants.
+pub fn check_predicate(
+    expr: &Expr,
+    column_type: &dyn Fn(&str) -> Option<String>,
+    enums: &[EnumIr],
+) -> Result<(), String> {
+    let ty = infer_type(expr, column_type)?;
+    if ty != BOOL_TYPE && ty != NULL_TYPE {
+        return Err(format!(
+            "expression '{}' must have type '{}', found '{}'",
+            expr, BOOL_TYPE, ty
+        ));
+    }
+    check_enum_literals(expr, column_type, enums)
+}
+
+fn check_enum_literals(
+    expr: &Expr,
+    column_type: &dyn Fn(&str) -> Option<String>,
+    enums: &[EnumIr],
+) -> Result<(), String> {
+    match expr {
+        Expr::Column(_)
+        | Expr::Integer(_)
+        | Expr::Text(_)
+        | Expr::Bool(_)
+        | Expr::Null => Ok(()),
+        Expr::Unary { operand, .. } | Expr::IsNull { operand, .. } => {
+            check_enum_literals(operand, column_type, enums)
+        }
+        Expr::Binary { left, right, .. } => {
+            if let Some((column, value)) = enum_comparison(left, right) {
+                let ty = column_type(column).unwrap_or_default();
+                if let Some(enum_def) =
+                    enums.iter().find(|enum_def| enum_def.name == ty)
+                {
+                    if !enum_def.variants.iter().any(|v| v == value) {
+                        return Err(format!(
+                            "'{}' is not a variant of enum '{}'",
+                            value, enum_def.name
+                        ));
+                    }
+                }
+            }
+            check_enum_literals(left, column_type, enums)?;
+            check_enum_literals(right, column_type, enums)
+        }
+        Expr::Call { args, .. } => {
+            for arg in args {
+                check_enum_literals(arg, column_type, enums)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+pub(crate) fn enum_comparison<'a>(
+    left: &'a Expr,
+    right: &'a Expr,
+) -> Option<(&'a str, &'a str)> {
+    match (left, right) {
+        (Expr::Column(column), Expr::Text(value))
+        | (Expr::Text(value), Expr::Column(column)) => Some((column, value)),
+        _ => None,
+    }
+}
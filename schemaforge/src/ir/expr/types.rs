@@ -0,0 +1,275 @@
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Expr {
+    Column(String),
+    Integer(i64),
+    Text(String),
+    Bool(bool),
+    Null,
+    Unary {
+        op: UnaryOp,
+        operand: Box<Expr>,
+    },
+    Binary {
+        op: BinaryOp,
+        left: Box<Expr>,
+        right: Box<Expr>,
+    },
+    IsNull {
+        operand: Box<Expr>,
+        negated: bool,
+    },
+    Call {
+        function: String,
+        args: Vec<Expr>,
+    },
+}
+
+pub const FUNCTIONS: [(&str, usize); 4] =
+    [("len", 1), ("lower", 1), ("upper", 1), ("abs", 1)];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UnaryOp {
+    Neg,
+    Not,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BinaryOp {
+    Or,
+    And,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Add,
+    Sub,
+    Concat,
+    Mul,
+    Div,
+    Rem,
+}
+
+impl BinaryOp {
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            BinaryOp::Or => "or",
+            BinaryOp::And => "and",
+            BinaryOp::Eq => "=",
+            BinaryOp::Ne => "!=",
+            BinaryOp::Lt => "<",
+            BinaryOp::Le => "<=",
+            BinaryOp::Gt => ">",
+            BinaryOp::Ge => ">=",
+            BinaryOp::Add => "+",
+            BinaryOp::Sub => "-",
+            BinaryOp::Concat => "||",
+            BinaryOp::Mul => "*",
+            BinaryOp::Div => "/",
+            BinaryOp::Rem => "%",
+        }
+    }
+
+    pub fn precedence(&self) -> u8 {
+        match self {
+            BinaryOp::Or => 1,
+            BinaryOp::And => 2,
+            BinaryOp::Eq
+            | BinaryOp::Ne
+            | BinaryOp::Lt
+            | BinaryOp::Le
+            | BinaryOp::Gt
+            | BinaryOp::Ge => 4,
+            BinaryOp::Add | BinaryOp::Sub | BinaryOp::Concat => 5,
+            BinaryOp::Mul | BinaryOp::Div | BinaryOp::Rem => 6,
+        }
+    }
+}
+
+impl Expr {
+    pub fn columns(&self) -> Vec<&str> {
+        let mut out = Vec::new();
+        self.collect_columns(&mut out);
+        out
+    }
+
+    pub fn check_calls(&self) -> Result<(), String> {
+        match self {
+            Expr::Column(_)
+            | Expr::Integer(_)
+            | Expr::Text(_)
+            | Expr::Bool(_)
+            | Expr::Null => Ok(()),
+            Expr::Unary { operand, .. } | Expr::IsNull { operand, .. } => {
+                operand.check_calls()
+            }
+            Expr::Binary { left, right, .. } => {
+                left.check_calls()?;
+                right.check_calls()
+            }
+            Expr::Call { function, args } => {
+                let arity = FUNCTIONS
+                    .iter()
+                    .find(|(name, _)| name == function)
+                    .map(|(_, arity)| *arity)
+                    .ok_or_else(|| {
+                        format!("unknown function '{}'", function)
+                    })?;
+                if args.len() != arity {
+                    return Err(format!(
+                        "function '{}' expects {} argument(s), got {}",
+                        function,
+                        arity,
+                        args.len()
+                    ));
+                }
+                for arg in args {
+                    arg.check_calls()?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    // Rewrites negative integer literals as negations, the form `parse_expr`
+    // gives `-1`, so that printed expressions parse back to equal values.
+    // `i64::MIN` has no positive counterpart and stays a literal.
+    pub fn canonical(&self) -> Expr {
+        match self {
+            Expr::Integer(value) if *value < 0 && *value != i64::MIN => {
+                Expr::Unary {
+                    op: UnaryOp::Neg,
+                    operand: Box::new(Expr::Integer(-value)),
+                }
+            }
+            Expr::Column(_)
+            | Expr::Integer(_)
+            | Expr::Text(_)
+            | Expr::Bool(_)
+            | Expr::Null => self.clone(),
+            Expr::Unary { op, operand } => Expr::Unary {
+                op: *op,
+                operand: Box::new(operand.canonical()),
+            },
+            Expr::Binary { op, left, right } => Expr::Binary {
+                op: *op,
+                left: Box::new(left.canonical()),
+                right: Box::new(right.canonical()),
+            },
+            Expr::IsNull { operand, negated } => Expr::IsNull {
+                operand: Box::new(operand.canonical()),
+                negated: *negated,
+            },
+            Expr::Call { function, args } => Expr::Call {
+                function: function.clone(),
+                args: args.iter().map(Expr::canonical).collect(),
+            },
+        }
+    }
+
+    fn collect_columns<'a>(&'a self, out: &mut Vec<&'a str>) {
+        match self {
+            Expr::Column(name) => {
+                if !out.contains(&name.as_str()) {
+                    out.push(name);
+                }
+            }
+            Expr::Integer(_) | Expr::Text(_) | Expr::Bool(_) | Expr::Null => {}
+            Expr::Unary { operand, .. } | Expr::IsNull { operand, .. } => {
+                operand.collect_columns(out)
+            }
+            Expr::Binary { left, right, .. } => {
+                left.collect_columns(out);
+                right.collect_columns(out);
+            }
+            Expr::Call { args, .. } => {
+                for arg in args {
+                    arg.collect_columns(out);
+                }
+            }
+        }
+    }
+
+    fn precedence(&self) -> u8 {
+        match self {
+            Expr::Binary { op, .. } => op.precedence(),
+            Expr::Unary {
+                op: UnaryOp::Not, ..
+            } => 3,
+            Expr::IsNull { .. } => 4,
+            _ => 7,
+        }
+    }
+}
+
+// Prints the expression in the source syntax accepted by `parse_expr`,
+// adding parentheses only where precedence requires them. Negative literals
+// print as `-1`, which parses as a negation; `canonical` gives the
+// expression that printing and parsing round-trips to.
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expr::Column(name) => write!(f, "{}", name),
+            Expr::Integer(value) => write!(f, "{}", value),
+            Expr::Text(value) => write!(f, "'{}'", value.replace('\'', "''")),
+            Expr::Bool(value) => write!(f, "{}", value),
+            Expr::Null => write!(f, "null"),
+            Expr::Unary { op, operand } => {
+                match op {
+                    UnaryOp::Neg => write!(f, "-")?,
+                    UnaryOp::Not => write!(f, "not ")?,
+                }
+                // `--5` would parse, but reads like a SQL comment.
+                if matches!(
+                    operand.as_ref(),
+                    Expr::Unary {
+                        op: UnaryOp::Neg,
+                        ..
+                    }
+                ) || matches!(operand.as_ref(), Expr::Integer(value) if *value < 0)
+                {
+                    return write!(f, "({})", operand);
+                }
+                write_operand(f, operand, self.precedence())
+            }
+            Expr::Binary { op, left, right } => {
+                write_operand(f, left, op.precedence())?;
+                write!(f, " {} ", op.symbol())?;
+                write_operand(f, right, op.precedence() + 1)
+            }
+            Expr::IsNull { operand, negated } => {
+                write_operand(f, operand, self.precedence() + 1)?;
+                if *negated {
+                    write!(f, " is not null")
+                } else {
+                    write!(f, " is null")
+                }
+            }
+            Expr::Call { function, args } => {
+                write!(f, "{}(", function)?;
+                for (index, arg) in args.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", arg)?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+fn write_operand(
+    f: &mut fmt::Formatter<'_>,
+    operand: &Expr,
+    min_precedence: u8,
+) -> fmt::Result {
+    if operand.precedence() < min_precedence {
+        write!(f, "({})", operand)
+    } else {
+        write!(f, "{}", operand)
+    }
+}
@@ -1,7 +1,8 @@
 mod kdl;
 mod types;
 
-pub use kdl::{parse_kdl, print_kdl};
+pub use kdl::{parse_kdl, print_kdl, print_kdl_with};
 pub use types::{
-    FieldIr, ProcIr, ProcParamIr, QueryIr, ResolvedSchema, SchemaIr, TableIr,
+    CheckIr, EnumIr, FieldIr, ProcIr, ProcParamIr, QueryIr, ResolvedSchema,
+    SchemaIr, TableIr,
 };
@@ -1,59 +1,134 @@
 use crate::error::Error;
-use crate::ir::schema::{FieldIr, SchemaIr, TableIr};
+use crate::ir::expr::{parse_expr, parse_expr_entry, Expr};
+use crate::ir::schema::{
+    CheckIr, EnumIr, FieldIr, ProcIr, ProcParamIr, QueryIr, SchemaIr, TableIr,
+};
+use crate::ir::PrintOrder;
 use crate::plan::ColumnId;
 use kdl::{KdlDocument, KdlEntry, KdlNode, KdlValue};
 
 pub fn parse_kdl(src: &str) -> Result<SchemaIr, Error> {
     let doc: KdlDocument = src.parse()?;
+    let mut enums = Vec::new();
     let mut tables = Vec::new();
 
     for node in doc.nodes() {
-        if node.name().value() != "table" {
-            return Err(Error::Parse(format!(
-                "unknown root node '{}', expected 'table'",
-                node.name().value()
-            )));
+        match node.name().value() {
+            "enum" => enums.push(parse_enum(node)?),
+            "table" => {
+                let table_id = tables.len();
+                tables.push(parse_table(node, table_id)?);
+            }
+            "proc" | "query" => {}
+            other => {
+                return Err(Error::Parse(format!(
+                "unknown root node '{}', expected 'enum', 'table', 'proc', or 'query'",
+                other
+            )))
+            }
         }
+    }
 
-        let table_id = tables.len();
-        tables.push(parse_table(node, table_id)?);
+    let mut procs = Vec::new();
+    let mut queries = Vec::new();
+    for node in doc.nodes() {
+        match node.name().value() {
+            "proc" => procs.push(parse_proc(node, &tables)?),
+            "query" => queries.push(parse_query(node, &tables)?),
+            _ => {}
+        }
     }
 
     Ok(SchemaIr {
+        enums,
         tables,
-        procs: Vec::new(),
-        queries: Vec::new(),
+        procs,
+        queries,
     })
 }
 
 pub fn print_kdl(value: &SchemaIr) -> String {
-    let mut tables = value.tables.clone();
-    tables.sort_by(|a, b| a.name.cmp(&b.name));
+    print_kdl_with(value, PrintOrder::Declaration)
+}
 
+pub fn print_kdl_with(value: &SchemaIr, order: PrintOrder) -> String {
+    let mut enums = value.enums.clone();
+    let mut tables = value.tables.clone();
     let mut procs = value.procs.clone();
-    procs.sort_by(|a, b| a.name.cmp(&b.name));
-
     let mut queries = value.queries.clone();
-    queries.sort_by(|a, b| a.name.cmp(&b.name));
+    if order == PrintOrder::Sorted {
+        enums.sort_by(|a, b| a.name.cmp(&b.name));
+        tables.sort_by(|a, b| a.name.cmp(&b.name));
+        procs.sort_by(|a, b| a.name.cmp(&b.name));
+        queries.sort_by(|a, b| a.name.cmp(&b.name));
+    }
 
     let mut out = String::new();
 
+    for enum_def in enums {
+        out.push_str(&format!(
+            "enum \"{}\" repr=\"{}\"",
+            escape(&enum_def.name),
+            escape(&enum_def.repr)
+        ));
+
+        if enum_def.variants.is_empty() {
+            out.push('\n');
+            continue;
+        }
+
+        out.push_str(" {\n");
+        for variant in &enum_def.variants {
+            out.push_str(&format!("  variant \"{}\"\n", escape(variant)));
+        }
+        out.push_str("}\n");
+    }
+
     for table in tables {
         let mut fields = table.fields.clone();
-        fields.sort_by(|a, b| a.name.cmp(&b.name));
+        if order == PrintOrder::Sorted {
+            fields.sort_by(|a, b| a.name.cmp(&b.name));
+        }
+
+        out.push_str(&format!("table \"{}\"", escape(&table.name)));
+        if order == PrintOrder::Declaration {
+            out.push_str(&format!(" id={}", table.id));
+        }
 
-        if fields.is_empty() {
-            out.push_str(&format!("table \"{}\"\n", escape(&table.name)));
+        if fields.is_empty() && table.checks.is_empty() {
+            out.push('\n');
             continue;
         }
 
-        out.push_str(&format!("table \"{}\" {{\n", escape(&table.name)));
+        out.push_str(" {\n");
         for field in fields {
             out.push_str(&format!(
-                "  field \"{}\" type=\"{}\"\n",
+                "  field \"{}\" type=\"{}\"",
                 escape(&field.name),
                 escape(&field.ty)
             ));
+            if order == PrintOrder::Declaration {
+                out.push_str(&format!(" column={}", field.id.column));
+            }
+            print_field_properties(
+                &mut out,
+                field.key,
+                field.auto_increment,
+                &field.default,
+                &field.computed,
+            );
+            if field.checks.is_empty() {
+                out.push('\n');
+                continue;
+            }
+            out.push_str(" {\n");
+            for check in &field.checks {
+                print_check(&mut out, check, "    ");
+            }
+            out.push_str("  }\n");
+        }
+        for check in &table.checks {
+            print_check(&mut out, check, "  ");
         }
         out.push_str("}\n");
     }
@@ -90,7 +165,7 @@ pub fn print_kdl(value: &SchemaIr) -> String {
             escape(table_name)
         ));
 
-        if query.projection.is_empty() {
+        if query.projection.is_empty() && query.filter.is_none() {
             out.push('\n');
             continue;
         }
@@ -100,31 +175,87 @@ pub fn print_kdl(value: &SchemaIr) -> String {
             let column_name = column_name(value, column_id);
             out.push_str(&format!("  project \"{}\"\n", escape(column_name)));
         }
+        if let Some(filter) = &query.filter {
+            out.push_str(&format!(
+                "  filter \"{}\"\n",
+                escape(&filter.to_string())
+            ));
+        }
         out.push_str("}\n");
     }
 
     out
 }
 
-fn parse_table(node: &KdlNode, table_id: usize) -> Result<TableIr, Error> {
-    let name = expect_single_string_value(node, "table")?;
-    ensure_no_properties(node, "table")?;
+fn parse_enum(node: &KdlNode) -> Result<EnumIr, Error> {
+    let name = expect_single_string_value(node, "enum")?;
+    ensure_only_properties(node, "enum", &["repr"], "")?;
+    let repr = optional_string_property(node, "repr")?
+        .unwrap_or_else(|| "text".to_string());
 
-    let mut fields = Vec::new();
+    let mut variants = Vec::new();
     if let Some(children) = node.children() {
         for child in children.nodes() {
-            if child.name().value() != "field" {
+            if child.name().value() != "variant" {
                 return Err(Error::Parse(format!(
-                    "unknown node '{}' in table '{}', expected 'field'",
+                    "unknown node '{}' in enum '{}', expected 'variant'",
                     child.name().value(),
                     name
                 )));
             }
-            let field_id = ColumnId {
-                table: table_id,
-                column: fields.len(),
-            };
-            fields.push(parse_field(child, &name, field_id)?);
+            variants.push(expect_single_string_value(child, "variant")?);
+            ensure_no_properties(child, "variant")?;
+            if child.children().is_some() {
+                return Err(Error::Parse(format!(
+                    "'variant' node in enum '{}' does not support children",
+                    name
+                )));
+            }
+        }
+    }
+
+    Ok(EnumIr {
+        name,
+        repr,
+        variants,
+    })
+}
+
+fn parse_table(node: &KdlNode, table_id: usize) -> Result<TableIr, Error> {
+    let name = expect_single_string_value(node, "table")?;
+    ensure_only_properties(node, "table", &["id"], "")?;
+    if let Some(declared) = expect_optional_index_property(node, "id")? {
+        if declared != table_id {
+            return Err(Error::Parse(format!(
+                "table '{}' declares id {} but is table {}",
+                name, declared, table_id
+            )));
+        }
+    }
+
+    let mut fields = Vec::new();
+    let mut checks = Vec::new();
+    if let Some(children) = node.children() {
+        for child in children.nodes() {
+            match child.name().value() {
+                "field" => {
+                    let field_id = ColumnId {
+                        table: table_id,
+                        column: fields.len(),
+                    };
+                    fields.push(parse_field(child, &name, field_id)?);
+                }
+                "check" => checks.push(parse_check(
+                    child,
+                    &format!("table '{}'", name),
+                )?),
+                other => {
+                    return Err(Error::Parse(format!(
+                        "unknown node '{}' in table '{}', expected 'field' or 'check'",
+                        other, name
+                    )))
+                }
+            }
         }
     }
 
@@ -132,6 +263,7 @@ fn parse_table(node: &KdlNode, table_id: usize) -> Result<TableIr, Error> {
         id: table_id,
         name,
         fields,
+        checks,
     })
 }
 
@@ -142,15 +274,206 @@ fn parse_field(
 ) -> Result<FieldIr, Error> {
     let name = expect_single_string_value(node, "field")?;
     let ty = expect_string_property(node, "type")?;
-    ensure_only_property(node, "field", "type", table_name)?;
+    ensure_only_properties(
+        node,
+        "field",
+        &[
+            "type",
+            "column",
+            "key",
+            "auto-increment",
+            "default",
+            "computed",
+        ],
+        &format!("table '{}'", table_name),
+    )?;
+    let key = optional_bool_property(node, "key")?;
+    let auto_increment = optional_bool_property(node, "auto-increment")?;
+    let default = optional_literal_property(node, "default")?;
+    let computed = optional_string_property(node, "computed")?
+        .map(|expr| parse_expr(&expr))
+        .transpose()?;
+    if let Some(declared) = expect_optional_index_property(node, "column")? {
+        if declared != field_id.column {
+            return Err(Error::Parse(format!(
+                "field '{}' in table '{}' declares column {} but is column {}",
+                name, table_name, declared, field_id.column
+            )));
+        }
+    }
+
+    let mut checks = Vec::new();
+    if let Some(children) = node.children() {
+        for child in children.nodes() {
+            if child.name().value() != "check" {
+                return Err(Error::Parse(format!(
+                    "unknown node '{}' in field '{}', expected 'check'",
+                    child.name().value(),
+                    name
+                )));
+            }
+            checks.push(parse_check(child, &format!("field '{}'", name))?);
+        }
+    }
 
     Ok(FieldIr {
         id: field_id,
         name,
         ty,
+        key,
+        auto_increment,
+        default,
+        computed,
+        checks,
+    })
+}
+
+fn parse_check(node: &KdlNode, owner: &str) -> Result<CheckIr, Error> {
+    let name = expect_string_property(node, "name")?;
+    ensure_only_properties(node, "check", &["name"], owner)?;
+
+    Ok(CheckIr {
+        name,
+        expr: parse_expr_entry(node, "check")?,
+    })
+}
+
+fn parse_proc(node: &KdlNode, tables: &[TableIr]) -> Result<ProcIr, Error> {
+    let name = expect_single_string_value(node, "proc")?;
+    let table_name = expect_string_property(node, "table")?;
+    ensure_only_properties(node, "proc", &["table"], "")?;
+    let table = find_table(tables, &table_name).ok_or_else(|| {
+        Error::Parse(format!(
+            "proc '{}' references unknown table '{}'",
+            name, table_name
+        ))
+    })?;
+
+    let mut params = Vec::new();
+    if let Some(children) = node.children() {
+        for child in children.nodes() {
+            if child.name().value() != "param" {
+                return Err(Error::Parse(format!(
+                    "unknown node '{}' in proc '{}', expected 'param'",
+                    child.name().value(),
+                    name
+                )));
+            }
+            params.push(parse_param(child, &name, table)?);
+        }
+    }
+
+    Ok(ProcIr {
+        name,
+        table: table.id,
+        params,
+    })
+}
+
+fn parse_param(
+    node: &KdlNode,
+    proc_name: &str,
+    table: &TableIr,
+) -> Result<ProcParamIr, Error> {
+    let name = expect_single_string_value(node, "param")?;
+    let ty = expect_string_property(node, "type")?;
+    ensure_only_properties(
+        node,
+        "param",
+        &["type"],
+        &format!("proc '{}'", proc_name),
+    )?;
+    let column = find_column(table, &name).ok_or_else(|| {
+        Error::Parse(format!(
+            "proc '{}' references unknown column '{}' in table '{}'",
+            proc_name, name, table.name
+        ))
+    })?;
+
+    Ok(ProcParamIr {
+        name,
+        ty,
+        column: column.id,
+    })
+}
+
+fn parse_query(node: &KdlNode, tables: &[TableIr]) -> Result<QueryIr, Error> {
+    let name = expect_single_string_value(node, "query")?;
+    let table_name = expect_string_property(node, "table")?;
+    ensure_only_properties(node, "query", &["table"], "")?;
+    let table = find_table(tables, &table_name).ok_or_else(|| {
+        Error::Parse(format!(
+            "query '{}' references unknown table '{}'",
+            name, table_name
+        ))
+    })?;
+
+    let mut projection = Vec::new();
+    let mut filter = None;
+    if let Some(children) = node.children() {
+        for child in children.nodes() {
+            match child.name().value() {
+                "project" => {}
+                "filter" => {
+                    filter = Some(parse_filter(child, &name, filter)?);
+                    continue;
+                }
+                other => {
+                    return Err(Error::Parse(format!(
+                        "unknown node '{}' in query '{}', expected 'project' or 'filter'",
+                        other, name
+                    )))
+                }
+            }
+            let column_name = expect_single_string_value(child, "project")?;
+            ensure_no_properties(child, "project")?;
+            if child.children().is_some() {
+                return Err(Error::Parse(format!(
+                    "'project' node in query '{}' does not support children",
+                    name
+                )));
+            }
+            let column = find_column(table, &column_name).ok_or_else(|| {
+                Error::Parse(format!(
+                    "query '{}' projects unknown column '{}' in table '{}'",
+                    name, column_name, table.name
+                ))
+            })?;
+            projection.push(column.id);
+        }
+    }
+
+    Ok(QueryIr {
+        name,
+        table: table.id,
+        projection,
+        filter,
     })
 }
 
+fn parse_filter(
+    node: &KdlNode,
+    query_name: &str,
+    previous: Option<Expr>,
+) -> Result<Expr, Error> {
+    if previous.is_some() {
+        return Err(Error::Parse(format!(
+            "query '{}' declares more than one filter",
+            query_name
+        )));
+    }
+    ensure_no_properties(node, "filter")?;
+    parse_expr_entry(node, "filter")
+}
+
+fn find_table<'a>(tables: &'a [TableIr], name: &str) -> Option<&'a TableIr> {
+    tables.iter().find(|table| table.name == name)
+}
+
+fn find_column<'a>(table: &'a TableIr, name: &str) -> Option<&'a FieldIr> {
+    table.fields.iter().find(|field| field.name == name)
+}
+
 fn expect_single_string_value(
     node: &KdlNode,
     kind: &str,
@@ -195,6 +518,121 @@ fn expect_string_property(node: &KdlNode, key: &str) -> Result<String, Error> {
     }
 }
 
+fn expect_optional_index_property(
+    node: &KdlNode,
+    key: &str,
+) -> Result<Option<usize>, Error> {
+    let entry = node.entries().iter().find(|entry| {
+        entry
+            .name()
+            .map(|name| name.value() == key)
+            .unwrap_or(false)
+    });
+
+    let entry = match entry {
+        Some(entry) => entry,
+        None => return Ok(None),
+    };
+
+    match entry.value() {
+        KdlValue::Base10(value) if *value >= 0 => Ok(Some(*value as usize)),
+        _ => Err(Error::Parse(format!(
+            "property '{}' must be a non-negative integer",
+            key
+        ))),
+    }
+}
+
+fn optional_bool_property(node: &KdlNode, key: &str) -> Result<bool, Error> {
+    let entry = node.entries().iter().find(|entry| {
+        entry
+            .name()
+            .map(|name| name.value() == key)
+            .unwrap_or(false)
+    });
+
+    match entry.map(|entry| entry.value()) {
+        None => Ok(false),
+        Some(KdlValue::Bool(value)) => Ok(*value),
+        Some(_) => Err(Error::Parse(format!(
+            "property '{}' must be a boolean",
+            key
+        ))),
+    }
+}
+
+fn optional_literal_property(
+    node: &KdlNode,
+    key: &str,
+) -> Result<Option<Expr>, Error> {
+    let entry = node.entries().iter().find(|entry| {
+        entry
+            .name()
+            .map(|name| name.value() == key)
+            .unwrap_or(false)
+    });
+
+    match entry.map(|entry| entry.value()) {
+        None => Ok(None),
+        Some(KdlValue::String(s)) => Ok(Some(Expr::Text(s.to_string()))),
+        Some(KdlValue::Base10(value)) => Ok(Some(Expr::Integer(*value))),
+        Some(KdlValue::Bool(value)) => Ok(Some(Expr::Bool(*value))),
+        Some(KdlValue::Null) => Ok(Some(Expr::Null)),
+        Some(_) => Err(Error::Parse(format!(
+            "property '{}' must be a string, integer, boolean, or null",
+            key
+        ))),
+    }
+}
+
+fn print_literal(value: &Expr) -> String {
+    match value {
+        Expr::Text(s) => format!("\"{}\"", escape(s)),
+        other => other.to_string(),
+    }
+}
+
+fn print_field_properties(
+    out: &mut String,
+    key: bool,
+    auto_increment: bool,
+    default: &Option<Expr>,
+    computed: &Option<Expr>,
+) {
+    if key {
+        out.push_str(" key=true");
+    }
+    if auto_increment {
+        out.push_str(" auto-increment=true");
+    }
+    if let Some(value) = default {
+        out.push_str(&format!(" default={}", print_literal(value)));
+    }
+    if let Some(expr) = computed {
+        out.push_str(&format!(" computed=\"{}\"", escape(&expr.to_string())));
+    }
+}
+
+fn optional_string_property(
+    node: &KdlNode,
+    key: &str,
+) -> Result<Option<String>, Error> {
+    let entry = node.entries().iter().find(|entry| {
+        entry
+            .name()
+            .map(|name| name.value() == key)
+            .unwrap_or(false)
+    });
+
+    match entry.map(|entry| entry.value()) {
+        None => Ok(None),
+        Some(KdlValue::String(s)) => Ok(Some(s.to_string())),
+        Some(_) => {
+            Err(Error::Parse(format!("property '{}' must be a string", key)))
+        }
+    }
+}
+
 fn ensure_no_properties(node: &KdlNode, kind: &str) -> Result<(), Error> {
     for entry in node.entries() {
         if let Some(name) = entry.name() {
@@ -208,19 +646,26 @@ fn ensure_no_properties(node: &KdlNode, kind: &str) -> Result<(), Error> {
     Ok(())
 }
 
-fn ensure_only_property(
+fn ensure_only_properties(
     node: &KdlNode,
     kind: &str,
-    property: &str,
-    table_name: &str,
+    allowed: &[&str],
+    parent: &str,
 ) -> Result<(), Error> {
     for entry in node.entries() {
         if let Some(name) = entry.name() {
-            if name.value() != property {
+            if !allowed.iter().any(|value| *value == name.value()) {
+                if parent.is_empty() {
+                    return Err(Error::Parse(format!(
+                        "'{}' node does not support property '{}'",
+                        kind,
+                        name.value()
+                    )));
+                }
                 return Err(Error::Parse(format!(
-                    "'{}' node in table '{}' does not support property '{}'",
+                    "'{}' node in {} does not support property '{}'",
                     kind,
-                    table_name,
+                    parent,
                     name.value()
                 )));
             }
@@ -229,6 +674,15 @@ fn ensure_only_property(
     Ok(())
 }
 
+fn print_check(out: &mut String, check: &CheckIr, indent: &str) {
+    out.push_str(&format!(
+        "{}check \"{}\" name=\"{}\"\n",
+        indent,
+        escape(&check.expr.to_string()),
+        escape(&check.name)
+    ));
+}
+
 fn table_name(schema: &SchemaIr, table_id: usize) -> &str {
     schema
         .tables
@@ -1,7 +1,9 @@
+use crate::ir::expr::Expr;
 use crate::plan::{ColumnId, TableId};
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct SchemaIr {
+    pub enums: Vec<EnumIr>,
     pub tables: Vec<TableIr>,
     pub procs: Vec<ProcIr>,
     pub queries: Vec<QueryIr>,
@@ -9,11 +11,19 @@ pub struct SchemaIr {
 
 pub type ResolvedSchema = SchemaIr;
 
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EnumIr {
+    pub name: String,
+    pub repr: String,
+    pub variants: Vec<String>,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct TableIr {
     pub id: TableId,
     pub name: String,
     pub fields: Vec<FieldIr>,
+    pub checks: Vec<CheckIr>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -21,6 +31,17 @@ pub struct FieldIr {
     pub id: ColumnId,
     pub name: String,
     pub ty: String,
+    pub key: bool,
+    pub auto_increment: bool,
+    pub default: Option<Expr>,
+    pub computed: Option<Expr>,
+    pub checks: Vec<CheckIr>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CheckIr {
+    pub name: String,
+    pub expr: Expr,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -42,6 +63,7 @@ pub struct QueryIr {
     pub name: String,
     pub table: TableId,
     pub projection: Vec<ColumnId>,
+    pub filter: Option<Expr>,
 }
 
 impl SchemaIr {
@@ -54,4 +76,8 @@ impl SchemaIr {
             .get(column_id.table)
             .and_then(|table| table.fields.get(column_id.column))
     }
+
+    pub fn enum_type(&self, name: &str) -> Option<&EnumIr> {
+        self.enums.iter().find(|enum_def| enum_def.name == name)
+    }
 }
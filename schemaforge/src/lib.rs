@@ -1,11 +1,17 @@
 pub mod backend;
 pub mod build;
+pub mod compat;
+pub mod diff;
 pub mod error;
+pub mod explain;
+pub mod format;
 pub mod ir;
+pub mod load;
 pub mod lower;
 pub mod passes;
 pub mod plan;
 pub mod registry;
 
+pub use build::compile_to_module;
 pub use error::format_for_tests;
 pub use error::Error;
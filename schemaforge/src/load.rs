@@ -0,0 +1,143 @@
+use crate::error::Error;
+use crate::ir;
+use crate::ir::ast::AstSchema;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LoadedSchema {
+    pub ast: AstSchema,
+    pub files: Vec<PathBuf>,
+}
+
+pub fn load_schema(path: &Path) -> Result<LoadedSchema, Error> {
+    load(path, None)
+}
+
+// Loads a schema whose root file is `path` but whose text is `text` rather
+// than what is on disk, e.g. an editor buffer with unsaved changes. Includes
+// are still read from disk, relative to `path`.
+pub fn load_schema_source(
+    path: &Path,
+    text: &str,
+) -> Result<LoadedSchema, Error> {
+    load(path, Some(text))
+}
+
+fn load(path: &Path, text: Option<&str>) -> Result<LoadedSchema, Error> {
+    let mut loader = Loader {
+        stack: Vec::new(),
+        loaded: HashSet::new(),
+        files: Vec::new(),
+        ast: AstSchema {
+            includes: Vec::new(),
+            mixins: Vec::new(),
+            enums: Vec::new(),
+            tables: Vec::new(),
+            procs: Vec::new(),
+            queries: Vec::new(),
+            lint_allow: Vec::new(),
+        },
+    };
+    loader.load(path, text)?;
+
+    Ok(LoadedSchema {
+        ast: loader.ast,
+        files: loader.files,
+    })
+}
+
+struct Loader {
+    stack: Vec<(PathBuf, PathBuf)>,
+    loaded: HashSet<PathBuf>,
+    files: Vec<PathBuf>,
+    ast: AstSchema,
+}
+
+impl Loader {
+    fn load(&mut self, path: &Path, text: Option<&str>) -> Result<(), Error> {
+        // A root given as text need not exist on disk yet.
+        let canonical = match (fs::canonicalize(path), text) {
+            (Ok(canonical), _) => canonical,
+            (Err(_), Some(_)) => path.to_path_buf(),
+            (Err(err), None) => {
+                return Err(Error::Parse(format!(
+                    "failed to read '{}': {}",
+                    path.display(),
+                    err
+                )))
+            }
+        };
+
+        if let Some(start) =
+            self.stack.iter().position(|(seen, _)| *seen == canonical)
+        {
+            let mut chain = self.stack[start..]
+                .iter()
+                .map(|(_, shown)| shown.display().to_string())
+                .collect::<Vec<_>>();
+            chain.push(path.display().to_string());
+            return Err(Error::Parse(format!(
+                "include cycle: {}",
+                chain.join(" -> ")
+            )));
+        }
+
+        if !self.loaded.insert(canonical.clone()) {
+            return Ok(());
+        }
+
+        let text = match text {
+            Some(text) => text.to_string(),
+            None => fs::read_to_string(path).map_err(|err| {
+                Error::Parse(format!(
+                    "failed to read '{}': {}",
+                    path.display(),
+                    err
+                ))
+            })?,
+        };
+        let shown = path.display().to_string();
+        let mut ast = ir::ast::parse_kdl(&text).map_err(|err| match err {
+            Error::Parse(message) => {
+                Error::Parse(format!("{}: {}", shown, message))
+            }
+            other => other,
+        })?;
+
+        self.files.push(path.to_path_buf());
+        self.stack.push((canonical, path.to_path_buf()));
+
+        let base = path.parent().unwrap_or_else(|| Path::new(""));
+        for include in std::mem::take(&mut ast.includes) {
+            self.load(&base.join(include), None)?;
+        }
+
+        self.stack.pop();
+
+        for mut mixin in ast.mixins {
+            mixin.source = Some(shown.clone());
+            self.ast.mixins.push(mixin);
+        }
+        for mut enum_def in ast.enums {
+            enum_def.source = Some(shown.clone());
+            self.ast.enums.push(enum_def);
+        }
+        for mut table in ast.tables {
+            table.source = Some(shown.clone());
+            self.ast.tables.push(table);
+        }
+        for mut proc_def in ast.procs {
+            proc_def.source = Some(shown.clone());
+            self.ast.procs.push(proc_def);
+        }
+        for mut query in ast.queries {
+            query.source = Some(shown.clone());
+            self.ast.queries.push(query);
+        }
+        self.ast.lint_allow.extend(ast.lint_allow);
+
+        Ok(())
+    }
+}
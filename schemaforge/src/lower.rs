@@ -35,8 +35,16 @@ fn lower_query(query: &QueryIr) -> Result<LoweredQuery, Error> {
         }
     }
 
+    let mut input = Plan::TableScan { table: query.table };
+    if let Some(predicate) = &query.filter {
+        input = Plan::Filter {
+            input: Box::new(input),
+            predicate: predicate.clone(),
+        };
+    }
+
     let plan = Plan::Project {
-        input: Box::new(Plan::TableScan { table: query.table }),
+        input: Box::new(input),
         columns: query.projection.clone(),
     };
 
@@ -1 +1,12 @@
+pub mod lint;
+pub mod mixins;
 pub mod resolve;
+
+use crate::error::Error;
+use crate::ir::ast::AstSchema;
+use crate::ir::schema::ResolvedSchema;
+
+pub fn run_frontend(input: &AstSchema) -> Result<ResolvedSchema, Error> {
+    let expanded = mixins::run(input)?;
+    resolve::run(&expanded)
+}
@@ -0,0 +1,115 @@
+use crate::error::Error;
+use crate::ir::schema::ResolvedSchema;
+use std::fmt;
+
+pub const LINT_RULES: [(&str, &str); 3] = [
+    ("naming-convention", "names must be snake_case"),
+    ("missing-key", "tables should declare a key field"),
+    (
+        "unused-table",
+        "tables should be written by at least one proc",
+    ),
+];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LintWarning {
+    pub rule: &'static str,
+    pub message: String,
+}
+
+impl fmt::Display for LintWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "warning: {} [{}]", self.message, self.rule)
+    }
+}
+
+pub fn run(
+    schema: &ResolvedSchema,
+    allow: &[String],
+) -> Result<Vec<LintWarning>, Error> {
+    for rule in allow {
+        if !LINT_RULES.iter().any(|(name, _)| name == rule) {
+            return Err(Error::Pass(format!(
+                "unknown lint rule '{}', expected one of: {}",
+                rule,
+                LINT_RULES
+                    .iter()
+                    .map(|(name, _)| *name)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )));
+        }
+    }
+    let enabled = |rule: &str| !allow.iter().any(|allowed| allowed == rule);
+
+    let mut warnings = Vec::new();
+
+    if enabled("naming-convention") {
+        let mut check = |kind: &str, name: &str| {
+            if !is_snake_case(name) {
+                warnings.push(LintWarning {
+                    rule: "naming-convention",
+                    message: format!("{} '{}' is not snake_case", kind, name),
+                });
+            }
+        };
+        for enum_def in &schema.enums {
+            check("enum", &enum_def.name);
+            for variant in &enum_def.variants {
+                check("enum variant", variant);
+            }
+        }
+        for table in &schema.tables {
+            check("table", &table.name);
+            for field in &table.fields {
+                check("field", &field.name);
+            }
+        }
+        for proc_def in &schema.procs {
+            check("proc", &proc_def.name);
+        }
+        for query in &schema.queries {
+            check("query", &query.name);
+        }
+    }
+
+    if enabled("missing-key") {
+        for table in &schema.tables {
+            if !table.fields.iter().any(|field| field.key) {
+                warnings.push(LintWarning {
+                    rule: "missing-key",
+                    message: format!("table '{}' has no key field", table.name),
+                });
+            }
+        }
+    }
+
+    if enabled("unused-table") {
+        for table in &schema.tables {
+            if !schema
+                .procs
+                .iter()
+                .any(|proc_def| proc_def.table == table.id)
+            {
+                warnings.push(LintWarning {
+                    rule: "unused-table",
+                    message: format!(
+                        "table '{}' is never written by any proc",
+                        table.name
+                    ),
+                });
+            }
+        }
+    }
+
+    Ok(warnings)
+}
+
+// Module-qualified names are checked one segment at a time.
+fn is_snake_case(name: &str) -> bool {
+    name.split('.').all(|segment| {
+        let mut chars = segment.chars();
+        matches!(chars.next(), Some('a'..='z'))
+            && chars.all(|ch| matches!(ch, 'a'..='z' | '0'..='9' | '_'))
+    })
+}
@@ -0,0 +1,94 @@
+use crate::error::Error;
+use crate::ir::ast::{AstField, AstMixin, AstSchema, AstTable};
+use std::collections::HashMap;
+
+pub fn run(input: &AstSchema) -> Result<AstSchema, Error> {
+    let mut mixins = HashMap::new();
+    for mixin in &input.mixins {
+        if mixins.insert(mixin.name.as_str(), mixin).is_some() {
+            return Err(Error::Pass(format!(
+                "duplicate mixin name '{}'",
+                mixin.name
+            )));
+        }
+    }
+
+    let mut tables = Vec::with_capacity(input.tables.len());
+    for table in &input.tables {
+        tables.push(expand_table(table, &mixins)?);
+    }
+
+    Ok(AstSchema {
+        includes: input.includes.clone(),
+        mixins: Vec::new(),
+        enums: input.enums.clone(),
+        tables,
+        procs: input.procs.clone(),
+        queries: input.queries.clone(),
+        lint_allow: input.lint_allow.clone(),
+    })
+}
+
+fn expand_table(
+    table: &AstTable,
+    mixins: &HashMap<&str, &AstMixin>,
+) -> Result<AstTable, Error> {
+    // Maps each field name to the mixin it came from, or None when the
+    // field is declared in the table itself.
+    let mut origins: HashMap<&str, Option<&str>> = HashMap::new();
+    for field in &table.fields {
+        origins.insert(field.name.as_str(), None);
+    }
+
+    let mut fields = Vec::new();
+    for position in 0..=table.fields.len() {
+        for table_use in table.uses.iter().filter(|u| u.position == position) {
+            let mixin =
+                mixins.get(table_use.mixin.as_str()).ok_or_else(|| {
+                    Error::Pass(format!(
+                        "table '{}' uses unknown mixin '{}'",
+                        table.name, table_use.mixin
+                    ))
+                })?;
+
+            for field in &mixin.fields {
+                if let Some(origin) =
+                    origins.insert(field.name.as_str(), Some(&mixin.name))
+                {
+                    return Err(conflict(table, field, mixin, origin));
+                }
+                fields.push(field.clone());
+            }
+        }
+
+        if let Some(field) = table.fields.get(position) {
+            fields.push(field.clone());
+        }
+    }
+
+    Ok(AstTable {
+        name: table.name.clone(),
+        fields,
+        uses: Vec::new(),
+        checks: table.checks.clone(),
+        source: table.source.clone(),
+    })
+}
+
+fn conflict(
+    table: &AstTable,
+    field: &AstField,
+    mixin: &AstMixin,
+    origin: Option<&str>,
+) -> Error {
+    match origin {
+        Some(other) => Error::Pass(format!(
+            "field '{}' from mixin '{}' conflicts with field from mixin '{}' in table '{}'",
+            field.name, mixin.name, other, table.name
+        )),
+        None => Error::Pass(format!(
+            "field '{}' from mixin '{}' conflicts with field declared in table '{}'",
+            field.name, mixin.name, table.name
+        )),
+    }
+}
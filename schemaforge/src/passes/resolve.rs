@@ -1,28 +1,97 @@
 use crate::error::Error;
-use crate::ir::ast::AstSchema;
+use crate::ir::ast::{AstCheck, AstField, AstSchema, AstTable};
+use crate::ir::expr::{check_predicate, infer_type, Expr, NULL_TYPE};
 use crate::ir::schema::{
-    FieldIr, ProcIr, ProcParamIr, QueryIr, SchemaIr, TableIr,
+    CheckIr, EnumIr, FieldIr, ProcIr, ProcParamIr, QueryIr, SchemaIr, TableIr,
 };
 use crate::plan::ColumnId;
 use std::collections::{HashMap, HashSet};
 
+const BUILTIN_TYPES: [&str; 2] = ["i64", "text"];
+
 pub fn run(input: &AstSchema) -> Result<SchemaIr, Error> {
+    if let Some(include) = input.includes.first() {
+        return Err(Error::Pass(format!(
+            "unresolved include '{}': includes must be loaded from a file",
+            include
+        )));
+    }
+
+    let mut enums = Vec::new();
+    let mut seen_enum_names = HashSet::new();
+    for enum_def in &input.enums {
+        if !seen_enum_names.insert(enum_def.name.clone()) {
+            return Err(Error::Pass(format!(
+                "duplicate enum name '{}'",
+                enum_def.name
+            )));
+        }
+
+        if BUILTIN_TYPES.contains(&enum_def.name.as_str()) {
+            return Err(Error::Pass(format!(
+                "enum '{}' shadows a built-in type",
+                enum_def.name
+            )));
+        }
+
+        if !BUILTIN_TYPES.contains(&enum_def.repr.as_str()) {
+            return Err(Error::Pass(format!(
+                "enum '{}' repr '{}' must be one of: {}",
+                enum_def.name,
+                enum_def.repr,
+                BUILTIN_TYPES.join(", ")
+            )));
+        }
+
+        if enum_def.variants.is_empty() {
+            return Err(Error::Pass(format!(
+                "enum '{}' must declare at least one variant",
+                enum_def.name
+            )));
+        }
+
+        let mut seen_variants = HashSet::new();
+        for variant in &enum_def.variants {
+            if !seen_variants.insert(variant.as_str()) {
+                return Err(Error::Pass(format!(
+                    "duplicate variant '{}' in enum '{}'",
+                    variant, enum_def.name
+                )));
+            }
+        }
+
+        enums.push(EnumIr {
+            name: enum_def.name.clone(),
+            repr: enum_def.repr.clone(),
+            variants: enum_def.variants.clone(),
+        });
+    }
+
     let mut table_name_to_id = HashMap::new();
     let mut tables = Vec::new();
 
     for table in &input.tables {
-        if table_name_to_id
-            .insert(table.name.clone(), tables.len())
-            .is_some()
+        if let Some(first_id) =
+            table_name_to_id.insert(table.name.clone(), tables.len())
         {
             return Err(Error::Pass(format!(
-                "duplicate table name '{}'",
-                table.name
+                "duplicate table name '{}'{}",
+                table.name,
+                duplicate_origin(&input.tables[first_id].source, &table.source)
+            )));
+        }
+
+        if let Some(table_use) = table.uses.first() {
+            return Err(Error::Pass(format!(
+                "table '{}' uses mixin '{}' but mixins have not been expanded",
+                table.name, table_use.mixin
             )));
         }
 
         let table_id = tables.len();
+        let mut key_field: Option<&str> = None;
         let mut seen_fields = HashSet::new();
+        let mut seen_checks = HashSet::new();
         let mut fields = Vec::new();
 
         for field in &table.fields {
@@ -33,6 +102,63 @@ pub fn run(input: &AstSchema) -> Result<SchemaIr, Error> {
                 )));
             }
 
+            if field.key {
+                if let Some(first) = key_field {
+                    return Err(Error::Pass(format!(
+                        "table '{}' declares more than one key field ('{}' and '{}')",
+                        table.name, first, field.name
+                    )));
+                }
+                key_field = Some(&field.name);
+            }
+
+            if field.auto_increment {
+                if !field.key {
+                    return Err(Error::Pass(format!(
+                        "auto-increment field '{}' in table '{}' must be a key",
+                        field.name, table.name
+                    )));
+                }
+                if field.ty != "i64" {
+                    return Err(Error::Pass(format!(
+                        "auto-increment field '{}' in table '{}' must have type 'i64', found '{}'",
+                        field.name, table.name, field.ty
+                    )));
+                }
+                if field.default.is_some() {
+                    return Err(Error::Pass(format!(
+                        "auto-increment field '{}' in table '{}' cannot declare a default",
+                        field.name, table.name
+                    )));
+                }
+            }
+
+            if let Some(default) = &field.default {
+                resolve_default(default, field, table, &enums)?;
+            }
+
+            if let Some(computed) = &field.computed {
+                resolve_computed(computed, field, table)?;
+            }
+
+            let mut checks = Vec::new();
+            for (index, check) in field.checks.iter().enumerate() {
+                let default_name = format!(
+                    "{}_{}_check_{}",
+                    table.name,
+                    field.name,
+                    index + 1
+                );
+                checks.push(resolve_check(
+                    check,
+                    default_name,
+                    table,
+                    Some(field),
+                    &enums,
+                    &mut seen_checks,
+                )?);
+            }
+
             fields.push(FieldIr {
                 id: ColumnId {
                     table: table_id,
@@ -40,23 +166,45 @@ pub fn run(input: &AstSchema) -> Result<SchemaIr, Error> {
                 },
                 name: field.name.clone(),
                 ty: field.ty.clone(),
+                key: field.key,
+                auto_increment: field.auto_increment,
+                default: field.default.clone(),
+                computed: field.computed.as_ref().map(Expr::canonical),
+                checks,
             });
         }
 
+        let mut checks = Vec::new();
+        for (index, check) in table.checks.iter().enumerate() {
+            let default_name = format!("{}_check_{}", table.name, index + 1);
+            checks.push(resolve_check(
+                check,
+                default_name,
+                table,
+                None,
+                &enums,
+                &mut seen_checks,
+            )?);
+        }
+
         tables.push(TableIr {
             id: table_id,
             name: table.name.clone(),
             fields,
+            checks,
         });
     }
 
     let mut procs = Vec::new();
-    let mut seen_proc_names = HashSet::new();
+    let mut seen_proc_names = HashMap::new();
     for proc_def in &input.procs {
-        if !seen_proc_names.insert(proc_def.name.clone()) {
+        if let Some(first_source) =
+            seen_proc_names.insert(proc_def.name.clone(), &proc_def.source)
+        {
             return Err(Error::Pass(format!(
-                "duplicate proc name '{}'",
-                proc_def.name
+                "duplicate proc name '{}'{}",
+                proc_def.name,
+                duplicate_origin(first_source, &proc_def.source)
             )));
         }
 
@@ -81,6 +229,20 @@ pub fn run(input: &AstSchema) -> Result<SchemaIr, Error> {
                 ))
             })?;
 
+            if column.auto_increment {
+                return Err(Error::Pass(format!(
+                    "proc '{}' param '{}' names auto-increment column in table '{}'; its value is assigned on insert",
+                    proc_def.name, param.name, table.name
+                )));
+            }
+
+            if column.computed.is_some() {
+                return Err(Error::Pass(format!(
+                    "proc '{}' param '{}' names computed column in table '{}'; its value is derived from other columns",
+                    proc_def.name, param.name, table.name
+                )));
+            }
+
             if param.ty != column.ty {
                 return Err(Error::Pass(format!(
                     "proc '{}' param '{}' type '{}' does not match table column type '{}'",
@@ -103,12 +265,15 @@ pub fn run(input: &AstSchema) -> Result<SchemaIr, Error> {
     }
 
     let mut queries = Vec::new();
-    let mut seen_query_names = HashSet::new();
+    let mut seen_query_names = HashMap::new();
     for query in &input.queries {
-        if !seen_query_names.insert(query.name.clone()) {
+        if let Some(first_source) =
+            seen_query_names.insert(query.name.clone(), &query.source)
+        {
             return Err(Error::Pass(format!(
-                "duplicate query name '{}'",
-                query.name
+                "duplicate query name '{}'{}",
+                query.name,
+                duplicate_origin(first_source, &query.source)
             )));
         }
 
@@ -132,20 +297,180 @@ pub fn run(input: &AstSchema) -> Result<SchemaIr, Error> {
             projection.push(column.id);
         }
 
+        if let Some(filter) = &query.filter {
+            let column_type = |name: &str| {
+                find_column(table, name).map(|field| field.ty.clone())
+            };
+            check_predicate(filter, &column_type, &enums).map_err(
+                |message| {
+                    Error::Pass(format!(
+                        "query '{}' filter on table '{}': {}",
+                        query.name, table.name, message
+                    ))
+                },
+            )?;
+        }
+
         queries.push(QueryIr {
             name: query.name.clone(),
             table: table_id,
             projection,
+            filter: query.filter.as_ref().map(Expr::canonical),
         });
     }
 
     Ok(SchemaIr {
+        enums,
         tables,
         procs,
         queries,
     })
 }
 
+fn resolve_check(
+    check: &AstCheck,
+    default_name: String,
+    table: &AstTable,
+    field: Option<&AstField>,
+    enums: &[EnumIr],
+    seen_checks: &mut HashSet<String>,
+) -> Result<CheckIr, Error> {
+    let name = check.name.clone().unwrap_or(default_name);
+    if !seen_checks.insert(name.clone()) {
+        return Err(Error::Pass(format!(
+            "duplicate check name '{}' in table '{}'",
+            name, table.name
+        )));
+    }
+
+    for column in check.expr.columns() {
+        match field {
+            Some(field) if column != field.name => {
+                return Err(Error::Pass(format!(
+                    "check '{}' on field '{}' in table '{}' may only reference column '{}'",
+                    name, field.name, table.name, field.name
+                )));
+            }
+            None if !table.fields.iter().any(|field| field.name == column) => {
+                return Err(Error::Pass(format!(
+                    "check '{}' in table '{}' references unknown column '{}'",
+                    name, table.name, column
+                )));
+            }
+            _ => {}
+        }
+    }
+
+    let column_type = |name: &str| {
+        table
+            .fields
+            .iter()
+            .find(|field| field.name == name)
+            .map(|field| field.ty.clone())
+    };
+    check_predicate(&check.expr, &column_type, enums).map_err(|message| {
+        Error::Pass(format!(
+            "check '{}' in table '{}': {}",
+            name, table.name, message
+        ))
+    })?;
+
+    Ok(CheckIr {
+        name,
+        expr: check.expr.canonical(),
+    })
+}
+
+fn resolve_default(
+    default: &Expr,
+    field: &AstField,
+    table: &AstTable,
+    enums: &[EnumIr],
+) -> Result<(), Error> {
+    let enum_def = enums.iter().find(|enum_def| enum_def.name == field.ty);
+    let valid = match (default, field.ty.as_str(), enum_def) {
+        (Expr::Integer(_), "i64", _) => true,
+        (Expr::Text(_), "text", _) => true,
+        (Expr::Text(value), _, Some(enum_def)) => {
+            if !enum_def.variants.contains(value) {
+                return Err(Error::Pass(format!(
+                    "default '{}' for field '{}' in table '{}' is not a variant of enum '{}'",
+                    value, field.name, table.name, enum_def.name
+                )));
+            }
+            true
+        }
+        _ => false,
+    };
+
+    if !valid {
+        return Err(Error::Pass(format!(
+            "default {} for field '{}' in table '{}' does not match type '{}'",
+            default, field.name, table.name, field.ty
+        )));
+    }
+
+    Ok(())
+}
+
+fn resolve_computed(
+    computed: &Expr,
+    field: &AstField,
+    table: &AstTable,
+) -> Result<(), Error> {
+    if field.key || field.default.is_some() {
+        return Err(Error::Pass(format!(
+            "computed field '{}' in table '{}' cannot be a key or declare a default",
+            field.name, table.name
+        )));
+    }
+
+    let column_type = |name: &str| {
+        table
+            .fields
+            .iter()
+            .find(|other| other.name == name)
+            .map(|other| other.ty.clone())
+    };
+    for column in computed.columns() {
+        let source = table.fields.iter().find(|other| other.name == column);
+        if source
+            .map(|other| other.computed.is_some())
+            .unwrap_or(false)
+        {
+            return Err(Error::Pass(format!(
+                "computed field '{}' in table '{}' references computed column '{}'",
+                field.name, table.name, column
+            )));
+        }
+    }
+
+    let ty = infer_type(computed, &column_type).map_err(|message| {
+        Error::Pass(format!(
+            "computed field '{}' in table '{}': {}",
+            field.name, table.name, message
+        ))
+    })?;
+    if ty != field.ty && ty != NULL_TYPE {
+        return Err(Error::Pass(format!(
+            "computed field '{}' in table '{}' has type '{}' but its expression has type '{}'",
+            field.name, table.name, field.ty, ty
+        )));
+    }
+
+    Ok(())
+}
+
+fn duplicate_origin(first: &Option<String>, second: &Option<String>) -> String {
+    match (first, second) {
+        (Some(first), Some(second)) => format!(
+            " (first declared in '{}', redeclared in '{}')",
+            first, second
+        ),
+        _ => String::new(),
+    }
+}
+
 fn find_column<'a>(table: &'a TableIr, name: &str) -> Option<&'a FieldIr> {
     table.fields.iter().find(|field| field.name == name)
 }
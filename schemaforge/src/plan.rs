@@ -1,3 +1,5 @@
+use crate::ir::expr::Expr;
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct ColumnId {
     pub table: TableId,
@@ -11,6 +13,10 @@ pub enum Plan {
     TableScan {
         table: TableId,
     },
+    Filter {
+        input: Box<Plan>,
+        predicate: Expr,
+    },
     Project {
         input: Box<Plan>,
         columns: Vec<ColumnId>,
@@ -1,5 +1,6 @@
 use crate::error::Error;
 use crate::ir;
+use crate::ir::PrintOrder;
 use crate::passes;
 
 pub struct PassSpec {
@@ -16,14 +17,44 @@ pub fn find_pass(name: &str) -> Option<&'static PassSpec> {
     PASS_REGISTRY.iter().find(|spec| spec.name == name)
 }
 
+fn run_expand_mixins(input: &str) -> Result<String, Error> {
+    let ast = ir::ast::parse_kdl(input)?;
+    let expanded = passes::mixins::run(&ast)?;
+    Ok(ir::ast::print_kdl_with(&expanded, PrintOrder::Declaration))
+}
+
 fn run_resolve(input: &str) -> Result<String, Error> {
     let ast = ir::ast::parse_kdl(input)?;
-    let schema = passes::resolve::run(&ast)?;
+    let schema = passes::run_frontend(&ast)?;
     Ok(ir::schema::print_kdl(&schema))
 }
 
-static PASS_REGISTRY: [PassSpec; 1] = [PassSpec {
-    name: "resolve",
-    help: "Resolve AST into Schema IR",
-    run: run_resolve,
-}];
+// Lint warnings are printed as KDL comments so the output stays a valid
+// (empty) schema document.
+fn run_lint(input: &str) -> Result<String, Error> {
+    let ast = ir::ast::parse_kdl(input)?;
+    let schema = passes::run_frontend(&ast)?;
+    let warnings = passes::lint::run(&schema, &ast.lint_allow)?;
+    Ok(warnings
+        .iter()
+        .map(|warning| format!("// {}\n", warning))
+        .collect())
+}
+
+static PASS_REGISTRY: [PassSpec; 3] = [
+    PassSpec {
+        name: "expand-mixins",
+        help: "Expand mixin uses into table fields",
+        run: run_expand_mixins,
+    },
+    PassSpec {
+        name: "resolve",
+        help: "Resolve AST into Schema IR",
+        run: run_resolve,
+    },
+    PassSpec {
+        name: "lint",
+        help: "Warn about missing keys, unused tables and naming",
+        run: run_lint,
+    },
+];
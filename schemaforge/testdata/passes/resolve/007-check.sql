@@ -0,0 +1,3 @@
+CREATE TABLE "people" ("id" INTEGER, "name" TEXT CONSTRAINT "name_not_empty" CHECK (length("name") > 0), "age" INTEGER CONSTRAINT "people_age_check_1" CHECK (("age" >= 0) AND ("age" < 150)), CONSTRAINT "people_check_1" CHECK (("name" <> 'root') OR ("age" > 18)));
+INSERT INTO "people" ("id", "name", "age") VALUES (?1, ?2, ?3);
+SELECT "name" FROM "people";
@@ -0,0 +1,4 @@
+CREATE TABLE "tickets" ("id" INTEGER PRIMARY KEY AUTOINCREMENT, "title" TEXT, "status" INTEGER DEFAULT 0 CHECK ("status" IN (0, 1)), "points" INTEGER DEFAULT -1, "owner" TEXT DEFAULT 'nobody');
+INSERT INTO "tickets" ("title", "points") VALUES (?1, COALESCE(?2, -1));
+INSERT INTO "tickets" ("title", "owner", "status") VALUES (?1, COALESCE(?2, 'nobody'), COALESCE(?3, 0));
+SELECT "id", "title", "status", "points", "owner" FROM "tickets";
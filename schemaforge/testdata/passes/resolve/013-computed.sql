@@ -0,0 +1,3 @@
+CREATE TABLE "people" ("first" TEXT, "last" TEXT, "full_name" TEXT GENERATED ALWAYS AS (("first" || ' ') || "last") STORED, "initials_len" INTEGER GENERATED ALWAYS AS (length("first") + length("last")) STORED);
+INSERT INTO "people" ("first", "last") VALUES (?1, ?2);
+SELECT "full_name", "initials_len" FROM "people";
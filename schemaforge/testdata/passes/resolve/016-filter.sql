@@ -0,0 +1,4 @@
+CREATE TABLE "tickets" ("id" INTEGER, "title" TEXT, "status" INTEGER CHECK ("status" IN (0, 1)), "points" INTEGER CONSTRAINT "tickets_points_check_1" CHECK ("points" >= 0));
+INSERT INTO "tickets" ("id", "title", "status", "points") VALUES (?1, ?2, ?3, ?4);
+SELECT "id", "title" FROM "tickets" WHERE ("status" = 0) AND ("points" > 1);
+SELECT "title" FROM "tickets" WHERE length("title") < 10;
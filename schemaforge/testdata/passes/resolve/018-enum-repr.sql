@@ -0,0 +1,3 @@
+CREATE TABLE "tickets" ("id" INTEGER, "status" TEXT CHECK ("status" IN ('active', 'closed')), "priority" INTEGER CHECK ("priority" IN (0, 1)));
+INSERT INTO "tickets" ("id", "status", "priority") VALUES (?1, ?2, ?3);
+SELECT "id", "status", "priority" FROM "tickets";
@@ -0,0 +1,3 @@
+CREATE TABLE "readings" ("id" INTEGER, "delta" INTEGER CONSTRAINT "readings_delta_check_1" CHECK ("delta" > -(100)), "offset" INTEGER CONSTRAINT "readings_offset_check_1" CHECK (-(-(5)) < "offset"), "scaled" INTEGER GENERATED ALWAYS AS ("delta" * -(2)) STORED);
+INSERT INTO "readings" ("id", "delta", "offset") VALUES (?1, ?2, ?3);
+SELECT "id" FROM "readings" WHERE "delta" < -(10);
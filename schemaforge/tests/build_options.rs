@@ -0,0 +1,239 @@
+use schemaforge::build::{build_with, BuildOptions, RusqliteDependency};
+use std::fs;
+use std::path::PathBuf;
+
+fn spike_fixture() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/spike/spike.in.kdl")
+}
+
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR"))
+        .join("build_options")
+        .join(name);
+    if dir.exists() {
+        fs::remove_dir_all(&dir).expect("remove old scratch dir");
+    }
+    dir
+}
+
+#[test]
+fn writes_to_configured_dir_with_crate_and_module_names() {
+    let output_dir = scratch_dir("named");
+    let options = BuildOptions {
+        output_dir: Some(output_dir.clone()),
+        crate_name: Some("people-db".to_string()),
+        module_name: Some("db".to_string()),
+        demo: true,
+        ..BuildOptions::default()
+    };
+
+    assert_eq!(
+        build_with(&spike_fixture(), &options).expect("build"),
+        output_dir
+    );
+    let cargo_toml =
+        fs::read_to_string(output_dir.join("Cargo.toml")).expect("Cargo.toml");
+    assert!(cargo_toml.contains("name = \"people-db\""));
+    assert_eq!(
+        fs::read_to_string(output_dir.join("src/lib.rs")).expect("lib.rs"),
+        "pub mod db;\n"
+    );
+    assert!(fs::read_to_string(output_dir.join("src/db.rs"))
+        .expect("db.rs")
+        .contains("pub struct Db {"));
+    assert!(fs::read_to_string(output_dir.join("src/main.rs"))
+        .expect("main.rs")
+        .contains("use people_db::db::{Db, Error};"));
+
+    // A directory schemaforge generated may be rebuilt.
+    build_with(&spike_fixture(), &options).expect("rebuild");
+}
+
+#[test]
+fn refuses_to_delete_unmarked_directories() {
+    let output_dir = scratch_dir("unmarked");
+    fs::create_dir_all(&output_dir).expect("create dir");
+    fs::write(output_dir.join("notes.txt"), "keep me").expect("write file");
+
+    let options = BuildOptions {
+        output_dir: Some(output_dir.clone()),
+        ..BuildOptions::default()
+    };
+    let err = build_with(&spike_fixture(), &options).expect_err("refuse");
+    assert!(err.to_string().contains("refusing to overwrite"));
+    assert!(output_dir.join("notes.txt").exists());
+}
+
+#[test]
+fn rejects_invalid_names() {
+    let output_dir = scratch_dir("invalid");
+    for options in [
+        BuildOptions {
+            output_dir: Some(output_dir.clone()),
+            crate_name: Some("9lives".to_string()),
+            ..BuildOptions::default()
+        },
+        BuildOptions {
+            output_dir: Some(output_dir.clone()),
+            module_name: Some("main".to_string()),
+            ..BuildOptions::default()
+        },
+    ] {
+        assert!(build_with(&spike_fixture(), &options).is_err());
+    }
+    assert!(!output_dir.exists());
+}
+
+#[test]
+fn builds_schemas_without_procs_or_queries() {
+    let dir = scratch_dir("partial");
+    fs::create_dir_all(&dir).expect("create dir");
+    let table = "table \"people\" {\n  field \"id\" type=\"i64\"\n}\n";
+    let schemas = [
+        ("tables", table.to_string()),
+        (
+            "procs",
+            format!(
+                "{}proc \"add\" table=\"people\" {{\n  param \"id\" type=\"i64\"\n}}\n",
+                table
+            ),
+        ),
+        (
+            "queries",
+            format!(
+                "{}query \"ids\" table=\"people\" {{\n  project \"id\"\n}}\n",
+                table
+            ),
+        ),
+    ];
+
+    for (name, schema) in schemas {
+        let input = dir.join(format!("{}.kdl", name));
+        fs::write(&input, schema).expect("write schema");
+        let output_dir = dir.join(name);
+        let options = BuildOptions {
+            output_dir: Some(output_dir.clone()),
+            demo: true,
+            ..BuildOptions::default()
+        };
+        build_with(&input, &options).expect("build");
+
+        let main_rs = fs::read_to_string(output_dir.join("src/main.rs"))
+            .expect("main.rs");
+        assert_eq!(main_rs.contains("db.add("), name == "procs");
+        assert_eq!(main_rs.contains("db.ids()"), name == "queries");
+    }
+}
+
+#[test]
+fn writes_configured_rusqlite_dependency() {
+    let cases = [
+        (
+            "pinned",
+            RusqliteDependency::Version {
+                version: "0.30".to_string(),
+                features: vec!["bundled".to_string(), "chrono".to_string()],
+            },
+            "rusqlite = { version = \"0.30\", features = [\"bundled\", \"chrono\"] }\n",
+            true,
+        ),
+        (
+            "workspace",
+            RusqliteDependency::Workspace {
+                features: Vec::new(),
+            },
+            "rusqlite = { workspace = true }\n",
+            false,
+        ),
+    ];
+
+    for (name, rusqlite, dependency, workspace_root) in cases {
+        let output_dir = scratch_dir(name);
+        let options = BuildOptions {
+            output_dir: Some(output_dir.clone()),
+            rusqlite,
+            ..BuildOptions::default()
+        };
+        build_with(&spike_fixture(), &options).expect("build");
+
+        let cargo_toml = fs::read_to_string(output_dir.join("Cargo.toml"))
+            .expect("Cargo.toml");
+        assert!(cargo_toml.contains(dependency), "{}", cargo_toml);
+        assert_eq!(cargo_toml.contains("[workspace]"), workspace_root);
+    }
+}
+
+#[test]
+fn module_only_writes_a_single_file() {
+    let output_dir = scratch_dir("module_only");
+    let options = BuildOptions {
+        output_dir: Some(output_dir.clone()),
+        module_name: Some("people".to_string()),
+        module_only: true,
+        ..BuildOptions::default()
+    };
+    build_with(&spike_fixture(), &options).expect("build");
+
+    let mut files = fs::read_dir(&output_dir)
+        .expect("read output dir")
+        .map(|entry| entry.expect("entry").file_name())
+        .collect::<Vec<_>>();
+    files.sort();
+    assert_eq!(files, [".schemaforge-generated", "people.rs"]);
+    assert!(fs::read_to_string(output_dir.join("people.rs"))
+        .expect("people.rs")
+        .contains("pub struct Db {"));
+}
+
+#[test]
+fn rejects_crate_options_for_module_only_output() {
+    let output_dir = scratch_dir("module_only_conflict");
+    for options in [
+        BuildOptions {
+            output_dir: Some(output_dir.clone()),
+            module_only: true,
+            demo: true,
+            ..BuildOptions::default()
+        },
+        BuildOptions {
+            output_dir: Some(output_dir.clone()),
+            module_only: true,
+            rusqlite: RusqliteDependency::Workspace {
+                features: Vec::new(),
+            },
+            ..BuildOptions::default()
+        },
+        BuildOptions {
+            output_dir: Some(output_dir.clone()),
+            rusqlite: RusqliteDependency::Version {
+                version: "0.31\"\nevil = \"1".to_string(),
+                features: Vec::new(),
+            },
+            ..BuildOptions::default()
+        },
+    ] {
+        assert!(build_with(&spike_fixture(), &options).is_err());
+    }
+    assert!(!output_dir.exists());
+}
+
+#[test]
+fn async_api_adds_async_db_and_futures_channel() {
+    let output_dir = scratch_dir("async");
+    let options = BuildOptions {
+        output_dir: Some(output_dir.clone()),
+        async_api: true,
+        ..BuildOptions::default()
+    };
+    build_with(&spike_fixture(), &options).expect("build");
+
+    let cargo_toml =
+        fs::read_to_string(output_dir.join("Cargo.toml")).expect("Cargo.toml");
+    assert!(cargo_toml.contains("futures-channel = \"0.3\"\n"));
+    let lib_rs =
+        fs::read_to_string(output_dir.join("src/lib.rs")).expect("lib.rs");
+    assert!(lib_rs.contains("pub struct AsyncDb {"));
+    assert!(lib_rs.contains("pub async fn insert_person(&self, "));
+    assert!(lib_rs.contains("pub async fn list_names_and_ids(&self)"));
+}
@@ -0,0 +1,247 @@
+use schemaforge::compat::{check_compat, Compatibility};
+use schemaforge::ir;
+use schemaforge::ir::schema::ResolvedSchema;
+use schemaforge::load::load_schema;
+use schemaforge::passes;
+use std::path::PathBuf;
+
+fn resolve_fixture(name: &str) -> ResolvedSchema {
+    let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let path = manifest_dir.join("tests/fixtures/diff").join(name);
+    let loaded = load_schema(&path).expect("load fixture");
+    passes::run_frontend(&loaded.ast).expect("resolve fixture")
+}
+
+fn resolve(input: &str) -> ResolvedSchema {
+    let ast = ir::ast::parse_kdl(input).expect("parse schema");
+    passes::run_frontend(&ast).expect("resolve schema")
+}
+
+#[test]
+fn classifies_api_breaking_changes() {
+    let changes =
+        check_compat(&resolve_fixture("old.kdl"), &resolve_fixture("new.kdl"))
+            .expect("check compat");
+    let classified = changes
+        .iter()
+        .map(|change| {
+            (
+                change.change.name.as_str(),
+                change.compatibility,
+                &change.reason,
+            )
+        })
+        .collect::<Vec<_>>();
+
+    assert!(classified.contains(&(
+        "people_ages",
+        Compatibility::Breaking,
+        &"removed query method".to_string()
+    )));
+    assert!(classified.contains(&(
+        "people_names",
+        Compatibility::Breaking,
+        &"changed tuple shape from (String,) to (String, i64)".to_string()
+    )));
+    assert!(classified.contains(&(
+        "add_person.age",
+        Compatibility::Breaking,
+        &"new required proc param".to_string()
+    )));
+    assert!(classified.contains(&(
+        "open_tickets",
+        Compatibility::Additive,
+        &"new query method".to_string()
+    )));
+}
+
+#[test]
+fn retargeted_query_with_same_shape_is_additive() {
+    let old = resolve(
+        r#"
+table "people" {
+    field "name" type="text"
+}
+table "staff" {
+    field "name" type="text"
+}
+query "names" table="people" {
+    project "name"
+}
+"#,
+    );
+    let new = resolve(
+        r#"
+table "people" {
+    field "name" type="text"
+}
+table "staff" {
+    field "name" type="text"
+}
+query "names" table="staff" {
+    project "name"
+}
+"#,
+    );
+
+    let changes = check_compat(&old, &new).expect("check compat");
+
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].compatibility, Compatibility::Additive);
+    assert_eq!(changes[0].reason, "same tuple shape (String,)");
+}
+
+#[test]
+fn swapped_params_are_breaking() {
+    let old = resolve(
+        r#"
+table "people" {
+    field "first" type="text"
+    field "last" type="text"
+}
+proc "add_person" table="people" {
+    param "first" type="text"
+    param "last" type="text"
+}
+"#,
+    );
+    let new = resolve(
+        r#"
+table "people" {
+    field "first" type="text"
+    field "last" type="text"
+}
+proc "add_person" table="people" {
+    param "last" type="text"
+    param "first" type="text"
+}
+"#,
+    );
+
+    let changes = check_compat(&old, &new).expect("check compat");
+    let classified = changes
+        .iter()
+        .map(|change| {
+            (
+                change.change.name.as_str(),
+                change.compatibility,
+                change.reason.as_str(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        classified,
+        vec![
+            (
+                "add_person.last",
+                Compatibility::Breaking,
+                "moved proc param from position 2 to 1"
+            ),
+            (
+                "add_person.first",
+                Compatibility::Breaking,
+                "moved proc param from position 1 to 2"
+            ),
+        ]
+    );
+}
+
+#[test]
+fn default_on_param_column_is_breaking() {
+    let old = resolve(
+        r#"
+table "people" {
+    field "name" type="text"
+    field "nickname" type="text"
+}
+proc "add_person" table="people" {
+    param "name" type="text"
+}
+"#,
+    );
+    let new = resolve(
+        r#"
+table "people" {
+    field "name" type="text" default="anon"
+    field "nickname" type="text" default="none"
+}
+proc "add_person" table="people" {
+    param "name" type="text"
+}
+"#,
+    );
+
+    let changes = check_compat(&old, &new).expect("check compat");
+
+    assert_eq!(changes.len(), 2);
+    assert_eq!(changes[0].change.name, "people.name");
+    assert_eq!(changes[0].compatibility, Compatibility::Breaking);
+    assert_eq!(
+        changes[0].reason,
+        "changed default, auto-increment or computed on a proc param column"
+    );
+    assert_eq!(changes[1].change.name, "people.nickname");
+    assert_eq!(changes[1].compatibility, Compatibility::Additive);
+    assert_eq!(
+        changes[1].reason,
+        "changed column default or computed value"
+    );
+}
+
+#[test]
+fn added_check_is_breaking() {
+    let old = resolve(
+        r#"
+table "people" {
+    field "age" type="i64"
+}
+"#,
+    );
+    let new = resolve(
+        r#"
+table "people" {
+    field "age" type="i64" {
+        check "age >= 0"
+    }
+}
+"#,
+    );
+
+    let changes = check_compat(&old, &new).expect("check compat");
+
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].compatibility, Compatibility::Breaking);
+    assert_eq!(
+        changes[0].reason,
+        "changed key or checks; inserts may now fail"
+    );
+}
+
+#[test]
+fn added_table_check_is_breaking() {
+    let old = resolve(
+        r#"
+table "people" {
+    field "age" type="i64"
+}
+"#,
+    );
+    let new = resolve(
+        r#"
+table "people" {
+    field "age" type="i64"
+    check "age >= 0"
+}
+"#,
+    );
+
+    let changes = check_compat(&old, &new).expect("check compat");
+
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].compatibility, Compatibility::Breaking);
+    assert_eq!(
+        changes[0].reason,
+        "changed table checks; inserts may now fail"
+    );
+}
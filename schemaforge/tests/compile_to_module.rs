@@ -0,0 +1,37 @@
+use schemaforge::compile_to_module;
+use std::fs;
+use std::path::PathBuf;
+
+fn fixture(path: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(path)
+}
+
+#[test]
+fn writes_a_single_module_file() {
+    let out_file = PathBuf::from(env!("CARGO_TARGET_TMPDIR"))
+        .join("compile_to_module/nested/schema.rs");
+    if out_file.exists() {
+        fs::remove_file(&out_file).expect("remove old module");
+    }
+
+    compile_to_module(&fixture("include/main.kdl"), &out_file)
+        .expect("compile to module");
+
+    let module = fs::read_to_string(&out_file).expect("read module");
+    assert!(module.starts_with("use rusqlite::{params, Connection};\n"));
+    assert!(module.contains("pub struct Db {"));
+    assert!(module.contains("pub fn insert_user("));
+    assert!(module.contains("pub fn billing_insert_invoice("));
+}
+
+#[test]
+fn reports_schema_errors() {
+    let out_file = PathBuf::from(env!("CARGO_TARGET_TMPDIR"))
+        .join("compile_to_module/cycle.rs");
+    let err = compile_to_module(&fixture("include/cycle_a.kdl"), &out_file)
+        .expect_err("include cycle");
+    assert!(err.to_string().contains("include cycle"));
+    assert!(!out_file.exists());
+}
@@ -0,0 +1,234 @@
+use schemaforge::diff::{diff_schemas, render_json, render_text};
+use schemaforge::ir;
+use schemaforge::ir::schema::ResolvedSchema;
+use schemaforge::load::load_schema;
+use schemaforge::passes;
+use std::path::PathBuf;
+
+fn resolve_fixture(name: &str) -> ResolvedSchema {
+    let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let path = manifest_dir.join("tests/fixtures/diff").join(name);
+    let loaded = load_schema(&path).expect("load fixture");
+    passes::run_frontend(&loaded.ast).expect("resolve fixture")
+}
+
+fn resolve(input: &str) -> ResolvedSchema {
+    let ast = ir::ast::parse_kdl(input).expect("parse schema");
+    passes::run_frontend(&ast).expect("resolve schema")
+}
+
+#[test]
+fn reports_added_removed_and_retyped_items() {
+    let changes =
+        diff_schemas(&resolve_fixture("old.kdl"), &resolve_fixture("new.kdl"));
+
+    assert_eq!(
+        render_text(&changes),
+        "\
+~ enum status: text [open, closed] -> text [open, closed, archived]
+- table notes
++ table tickets
+~ field people.age: i64 -> text
++ field people.email: text, default ''
+- proc add_note: insert into notes
++ param add_person.email: text #3
++ param add_person.age: text #4
+- query people_ages: people (age: i64)
+~ query people_names: people (name: text) -> people (name: text, id: i64)
++ query open_tickets: tickets (status: status)
+"
+    );
+}
+
+#[test]
+fn identical_schemas_have_no_changes() {
+    let schema = resolve_fixture("old.kdl");
+    let changes = diff_schemas(&schema, &schema);
+
+    assert!(changes.is_empty());
+    assert_eq!(render_json(&changes), "{\n  \"changes\": []\n}\n");
+}
+
+#[test]
+fn renders_json() {
+    let changes =
+        diff_schemas(&resolve_fixture("old.kdl"), &resolve_fixture("new.kdl"));
+    let json = render_json(&changes);
+
+    assert!(json.starts_with("{\n  \"changes\": [\n    {\"change\": \"retyped\", \"item\": \"enum\", \"name\": \"status\", \"old\": \"text [open, closed]\", \"new\": \"text [open, closed, archived]\"},\n"));
+    assert!(json.contains("{\"change\": \"removed\", \"item\": \"table\", \"name\": \"notes\", \"old\": null, \"new\": null}"));
+    assert!(json.ends_with("\"new\": \"tickets (status: status)\"}\n  ]\n}\n"));
+}
+
+#[test]
+fn reports_swapped_params() {
+    let old = resolve(
+        r#"
+table "people" {
+    field "first" type="text"
+    field "last" type="text"
+}
+proc "add_person" table="people" {
+    param "first" type="text"
+    param "last" type="text"
+}
+"#,
+    );
+    let new = resolve(
+        r#"
+table "people" {
+    field "first" type="text"
+    field "last" type="text"
+}
+proc "add_person" table="people" {
+    param "last" type="text"
+    param "first" type="text"
+}
+"#,
+    );
+
+    assert_eq!(
+        render_text(&diff_schemas(&old, &new)),
+        "\
+~ param add_person.last: text #2 -> text #1
+~ param add_person.first: text #1 -> text #2
+"
+    );
+}
+
+#[test]
+fn reports_field_attribute_changes() {
+    let old = resolve(
+        r#"
+table "people" {
+    field "id" type="i64"
+    field "name" type="text"
+    field "age" type="i64"
+    field "score" type="i64"
+}
+"#,
+    );
+    let new = resolve(
+        r#"
+table "people" {
+    field "id" type="i64" key=true auto-increment=true
+    field "name" type="text" default="anon"
+    field "age" type="i64" {
+        check "age >= 0" name="age_positive"
+    }
+    field "score" type="i64" computed="age * 2"
+}
+"#,
+    );
+
+    assert_eq!(
+        render_text(&diff_schemas(&old, &new)),
+        "\
+~ field people.id: i64 -> i64, key, auto-increment
+~ field people.name: text -> text, default 'anon'
+~ field people.age: i64 -> i64, check age_positive age >= 0
+~ field people.score: i64 -> i64, computed age * 2
+"
+    );
+}
+
+#[test]
+fn reports_changed_query_filter() {
+    let old = resolve(
+        r#"
+table "people" {
+    field "name" type="text"
+    field "age" type="i64"
+}
+query "adults" table="people" {
+    project "name"
+    filter "age >= 18"
+}
+"#,
+    );
+    let new = resolve(
+        r#"
+table "people" {
+    field "name" type="text"
+    field "age" type="i64"
+}
+query "adults" table="people" {
+    project "name"
+    filter "age >= 21"
+}
+"#,
+    );
+
+    assert_eq!(
+        render_text(&diff_schemas(&old, &new)),
+        "~ query adults: people (name: text) where age >= 18 -> people (name: \
+         text) where age >= 21\n"
+    );
+}
+
+#[test]
+fn reports_swapped_query_columns_of_the_same_type() {
+    let old = resolve(
+        r#"
+table "people" {
+    field "first" type="text"
+    field "last" type="text"
+}
+query "names" table="people" {
+    project "first"
+}
+"#,
+    );
+    let new = resolve(
+        r#"
+table "people" {
+    field "first" type="text"
+    field "last" type="text"
+}
+query "names" table="people" {
+    project "last"
+}
+"#,
+    );
+
+    assert_eq!(
+        render_text(&diff_schemas(&old, &new)),
+        "~ query names: people (first: text) -> people (last: text)\n"
+    );
+}
+
+#[test]
+fn reports_changed_table_checks() {
+    let old = resolve(
+        r#"
+table "people" {
+    field "name" type="text"
+    field "age" type="i64"
+}
+table "pets" {
+    field "age" type="i64"
+    check "age < 40" name="pets_young"
+}
+"#,
+    );
+    let new = resolve(
+        r#"
+table "people" {
+    field "name" type="text"
+    field "age" type="i64"
+    check "name <> 'root' or age > 18" name="root_is_adult"
+}
+table "pets" {
+    field "age" type="i64"
+}
+"#,
+    );
+
+    assert_eq!(
+        render_text(&diff_schemas(&old, &new)),
+        "\
+~ table people: check root_is_adult name != 'root' or age > 18
+~ table pets: check pets_young age < 40
+"
+    );
+}
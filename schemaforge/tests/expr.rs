@@ -0,0 +1,95 @@
+use schemaforge::explain::render_plan_tree;
+use schemaforge::ir;
+use schemaforge::ir::expr::{infer_type, parse_expr, print_rust};
+use schemaforge::lower::lower_queries;
+use schemaforge::passes;
+use std::fs;
+use std::path::PathBuf;
+
+fn load_fixture() -> ir::schema::ResolvedSchema {
+    let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let fixture =
+        manifest_dir.join("testdata/passes/resolve/016-filter.in.kdl");
+    let input = fs::read_to_string(&fixture).expect("read fixture");
+    let ast = ir::ast::parse_kdl(&input).expect("parse fixture");
+    passes::run_frontend(&ast).expect("resolve fixture")
+}
+
+#[test]
+fn nested_nodes_parse_like_strings() {
+    let schema = load_fixture();
+
+    assert_eq!(
+        schema.tables[0].fields[3].checks[0].expr,
+        parse_expr("points >= 0").expect("parse expr")
+    );
+    assert_eq!(
+        schema.queries[1].filter,
+        Some(parse_expr("len(title) < 10").expect("parse expr"))
+    );
+}
+
+#[test]
+fn renders_filters_in_plan_trees() {
+    let schema = load_fixture();
+    let lowered = lower_queries(&schema).expect("lower queries");
+
+    assert_eq!(
+        render_plan_tree(&lowered[1].plan, &schema),
+        "Project tickets.title\n  Filter len(title) < 10\n    TableScan tickets\n"
+    );
+}
+
+#[test]
+fn infers_expression_types() {
+    let column_type = |name: &str| match name {
+        "first" | "last" => Some("text".to_string()),
+        "age" => Some("i64".to_string()),
+        _ => None,
+    };
+    let infer = |src: &str| {
+        infer_type(&parse_expr(src).expect("parse expr"), &column_type)
+    };
+
+    assert_eq!(infer("first || ' ' || last"), Ok("text".to_string()));
+    assert_eq!(infer("age * 2 + len(first)"), Ok("i64".to_string()));
+    assert_eq!(
+        infer("age > 3 and first is not null"),
+        Ok("bool".to_string())
+    );
+    assert_eq!(
+        infer("age = first"),
+        Err("cannot compare 'i64' with 'text' in 'age = first'".to_string())
+    );
+    assert_eq!(
+        infer("not age"),
+        Err("operand of 'not age' must have type 'bool', found 'i64'"
+            .to_string())
+    );
+    assert_eq!(
+        infer("height > 1"),
+        Err("unknown column 'height'".to_string())
+    );
+}
+
+#[test]
+fn prints_rust_source() {
+    let rust = |src: &str| {
+        print_rust(&parse_expr(src).expect("parse expr"), &|name| {
+            format!("row.{}", name)
+        })
+    };
+
+    assert_eq!(
+        rust("len(first || last) > 3 or not (age = -1)"),
+        "(((format!(\"{}{}\", row.first, row.last)).chars().count() as i64) > 3i64) || (!(row.age == (-1i64)))"
+    );
+    assert_eq!(
+        rust("nickname is not null and lower(first) = 'ada'"),
+        "row.nickname.is_some() && (row.first.to_lowercase() == \"ada\")"
+    );
+    assert_eq!(
+        rust("-(-5) < abs(age) * 2"),
+        "(-(-5i64)) < (row.age.abs() * 2i64)"
+    );
+}
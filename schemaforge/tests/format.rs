@@ -0,0 +1,63 @@
+use schemaforge::format::format_schema;
+use schemaforge::ir::ast::parse_kdl;
+use std::fs;
+use std::path::PathBuf;
+
+fn read_fixture(name: &str) -> String {
+    let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    fs::read_to_string(manifest_dir.join("tests/fixtures/format").join(name))
+        .expect("read fixture")
+}
+
+#[test]
+fn formats_messy_schema() {
+    let formatted = format_schema(&read_fixture("messy.kdl")).expect("format");
+    assert_eq!(formatted, read_fixture("formatted.kdl"));
+}
+
+#[test]
+fn formatting_is_idempotent() {
+    let formatted = read_fixture("formatted.kdl");
+    assert_eq!(format_schema(&formatted).expect("format"), formatted);
+}
+
+#[test]
+fn formatting_preserves_the_schema() {
+    let messy = parse_kdl(&read_fixture("messy.kdl")).expect("parse messy");
+    let formatted =
+        parse_kdl(&read_fixture("formatted.kdl")).expect("parse formatted");
+    assert_eq!(messy, formatted);
+}
+
+#[test]
+fn quotes_names_that_are_not_identifiers() {
+    let formatted = format_schema(
+        "table \"my table\" {\n  field \"1st\"   type=\"i64\"\n}\n",
+    )
+    .expect("format");
+    assert_eq!(
+        formatted,
+        "table \"my table\" {\n    field \"1st\" type=\"i64\"\n}\n"
+    );
+}
+
+#[test]
+fn rejects_invalid_source() {
+    let err = format_schema("table \"people\" {\n").expect_err("unterminated");
+    assert_eq!(
+        err.to_string(),
+        "parse error: Expected closing '}' in node children block."
+    );
+}
+
+#[test]
+fn rejects_unterminated_last_child() {
+    let err = format_schema(
+        "enum \"status\" { variant \"open\"; variant \"closed\" }\n",
+    )
+    .expect_err("missing terminator");
+    assert_eq!(
+        err.to_string(),
+        "parse error: Expected valid node terminator."
+    );
+}
@@ -1,4 +1,9 @@
-use schemaforge::{format_for_tests, registry};
+use schemaforge::backend::sqlite::{
+    compile_create_table_sql, compile_insert_proc_sql, compile_plan_to_sql,
+};
+use schemaforge::ir::ast::parse_kdl;
+use schemaforge::lower::lower_queries;
+use schemaforge::{format_for_tests, passes, registry, Error};
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -94,6 +99,39 @@ fn golden_passes() {
 
             let result = (spec.run)(&input);
 
+            // An optional `<case>.sql` holds the SQLite statements compiled
+            // from the resolved input: CREATE TABLE for every table, then the
+            // INSERT of every proc and the SELECT of every query.
+            let sql_path = pass_dir.join(format!("{}.sql", base));
+            if sql_path.exists() {
+                let expected = fs::read_to_string(&sql_path).unwrap_or_else(|read_err| {
+                    panic!(
+                        "failed reading pass '{}' case '{}' expected sql {}: {}",
+                        pass_name,
+                        base,
+                        sql_path.display(),
+                        read_err
+                    )
+                });
+                let actual = compile_sql(&input).unwrap_or_else(|err| {
+                    panic!(
+                        "pass '{}' case '{}' failed to compile sql:\n{}",
+                        pass_name,
+                        base,
+                        format_for_tests(&err)
+                    )
+                });
+                if actual != expected {
+                    panic!(
+                        "pass '{}' case '{}' sql mismatch\n--- expected ---\n{}\n--- actual ---\n{}",
+                        pass_name,
+                        base,
+                        expected,
+                        actual
+                    );
+                }
+            }
+
             if has_out {
                 let expected = fs::read_to_string(&out_path).unwrap_or_else(|read_err| {
                     panic!(
@@ -158,6 +196,24 @@ fn golden_passes() {
     }
 }
 
+fn compile_sql(input: &str) -> Result<String, Error> {
+    let schema = passes::run_frontend(&parse_kdl(input)?)?;
+    let mut out = String::new();
+    for table in &schema.tables {
+        out.push_str(&compile_create_table_sql(table, &schema)?);
+        out.push_str(";\n");
+    }
+    for proc_def in &schema.procs {
+        out.push_str(&compile_insert_proc_sql(proc_def, &schema)?);
+        out.push_str(";\n");
+    }
+    for query in lower_queries(&schema)? {
+        out.push_str(&compile_plan_to_sql(&query.plan, &schema)?.sql);
+        out.push_str(";\n");
+    }
+    Ok(out)
+}
+
 fn read_sorted_dirs(path: &Path) -> Result<Vec<PathBuf>, std::io::Error> {
     let mut entries = Vec::new();
     for entry in fs::read_dir(path)? {
@@ -0,0 +1,99 @@
+use schemaforge::build::{build_with, BuildOptions};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+struct Scratch {
+    schema: PathBuf,
+    output_dir: PathBuf,
+}
+
+fn scratch(name: &str) -> Scratch {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR"))
+        .join("incremental")
+        .join(name);
+    if dir.exists() {
+        fs::remove_dir_all(&dir).expect("remove old scratch dir");
+    }
+    fs::create_dir_all(&dir).expect("create scratch dir");
+
+    let schema = dir.join("schema.kdl");
+    let fixture = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/spike/spike.in.kdl");
+    fs::copy(fixture, &schema).expect("copy fixture");
+    Scratch {
+        schema,
+        output_dir: dir.join("out"),
+    }
+}
+
+fn options(scratch: &Scratch, module_name: Option<&str>) -> BuildOptions {
+    BuildOptions {
+        output_dir: Some(scratch.output_dir.clone()),
+        module_name: module_name.map(str::to_string),
+        ..BuildOptions::default()
+    }
+}
+
+fn modified(path: &Path) -> SystemTime {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .expect("modification time")
+}
+
+#[test]
+fn unchanged_schema_rewrites_nothing() {
+    let scratch = scratch("unchanged");
+    build_with(&scratch.schema, &options(&scratch, None)).expect("build");
+
+    let files =
+        ["Cargo.toml", "src/lib.rs"].map(|file| scratch.output_dir.join(file));
+    let before = files.each_ref().map(|file| modified(file));
+    build_with(&scratch.schema, &options(&scratch, None)).expect("rebuild");
+    assert_eq!(files.each_ref().map(|file| modified(file)), before);
+
+    let manifest =
+        fs::read_to_string(scratch.output_dir.join(".schemaforge-generated"))
+            .expect("manifest");
+    assert!(manifest.lines().any(|line| line.starts_with("hash ")));
+    assert!(manifest.contains("file src/lib.rs\n"));
+}
+
+#[test]
+fn changed_schema_rewrites_only_changed_files() {
+    let scratch = scratch("changed");
+    build_with(&scratch.schema, &options(&scratch, None)).expect("build");
+    let cargo_toml = scratch.output_dir.join("Cargo.toml");
+    let cargo_toml_before = modified(&cargo_toml);
+
+    let mut schema = fs::read_to_string(&scratch.schema).expect("schema");
+    schema.push_str(
+        "\nquery \"ages\" table=\"people\" {\n  project \"age\"\n}\n",
+    );
+    fs::write(&scratch.schema, schema).expect("edit schema");
+    build_with(&scratch.schema, &options(&scratch, None)).expect("rebuild");
+
+    assert_eq!(modified(&cargo_toml), cargo_toml_before);
+    assert!(fs::read_to_string(scratch.output_dir.join("src/lib.rs"))
+        .expect("lib.rs")
+        .contains("pub fn ages("));
+}
+
+#[test]
+fn regenerates_missing_files_and_removes_stale_ones() {
+    let scratch = scratch("stale");
+    build_with(&scratch.schema, &options(&scratch, Some("db"))).expect("build");
+    let module = scratch.output_dir.join("src/db.rs");
+    assert!(module.exists());
+
+    fs::remove_file(&module).expect("delete module");
+    build_with(&scratch.schema, &options(&scratch, Some("db")))
+        .expect("rebuild");
+    assert!(module.exists());
+
+    build_with(&scratch.schema, &options(&scratch, None)).expect("rebuild");
+    assert!(!module.exists());
+    assert!(fs::read_to_string(scratch.output_dir.join("src/lib.rs"))
+        .expect("lib.rs")
+        .contains("pub struct Db {"));
+}
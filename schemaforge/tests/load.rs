@@ -0,0 +1,77 @@
+use schemaforge::format_for_tests;
+use schemaforge::load::load_schema;
+use schemaforge::passes;
+use std::path::PathBuf;
+
+fn fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/include")
+        .join(name)
+}
+
+#[test]
+fn loads_includes_relative_to_including_file() {
+    let loaded = load_schema(&fixture("main.kdl")).expect("load schema");
+    let schema = passes::resolve::run(&loaded.ast).expect("resolve schema");
+
+    let table_names = schema
+        .tables
+        .iter()
+        .map(|table| table.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(table_names, vec!["users", "billing.invoice"]);
+
+    let file_names = loaded
+        .files
+        .iter()
+        .map(|path| path.file_name().unwrap().to_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(file_names, vec!["main.kdl", "common.kdl", "billing.kdl"]);
+}
+
+#[test]
+fn qualifies_module_names_and_table_references() {
+    let loaded = load_schema(&fixture("main.kdl")).expect("load schema");
+    let schema = passes::resolve::run(&loaded.ast).expect("resolve schema");
+
+    let insert_invoice = schema
+        .procs
+        .iter()
+        .find(|proc_def| proc_def.name == "billing.insert_invoice")
+        .expect("module proc is qualified");
+    assert_eq!(schema.tables[insert_invoice.table].name, "billing.invoice");
+
+    let list_users = schema
+        .queries
+        .iter()
+        .find(|query| query.name == "billing.list_users")
+        .expect("module query is qualified");
+    assert_eq!(schema.tables[list_users.table].name, "users");
+}
+
+#[test]
+fn reports_include_cycles() {
+    let err = load_schema(&fixture("cycle_a.kdl")).expect_err("cycle");
+    let rendered = format_for_tests(&err);
+    assert!(
+        rendered.contains("include cycle: ") && rendered.contains(" -> "),
+        "{}",
+        rendered
+    );
+}
+
+#[test]
+fn reports_source_files_of_duplicates() {
+    let loaded = load_schema(&fixture("duplicate.kdl")).expect("load schema");
+    let err = passes::resolve::run(&loaded.ast).expect_err("duplicate");
+    let rendered = format_for_tests(&err);
+    assert!(
+        rendered.starts_with(
+            "pass error: duplicate table name 'users' (first declared in '"
+        ),
+        "{}",
+        rendered
+    );
+    assert!(rendered.contains("common.kdl"), "{}", rendered);
+    assert!(rendered.contains("duplicate.kdl"), "{}", rendered);
+}
@@ -0,0 +1,453 @@
+use proptest::prelude::*;
+use proptest::sample::Index;
+use schemaforge::ir;
+use schemaforge::ir::ast::{
+    AstCheck, AstEnum, AstField, AstParam, AstProc, AstQuery, AstSchema,
+    AstTable,
+};
+use schemaforge::ir::expr::{BinaryOp, Expr, UnaryOp};
+use schemaforge::ir::PrintOrder;
+use schemaforge::passes;
+
+// Words the expression parser reads as operators or literals, so they
+// cannot name a column referenced from an expression.
+const KEYWORDS: [&str; 7] = ["and", "or", "not", "is", "null", "true", "false"];
+
+fn ident() -> impl Strategy<Value = String> {
+    "[a-z][a-z0-9_]{0,7}"
+        .prop_filter("keyword", |name| !KEYWORDS.contains(&name.as_str()))
+}
+
+fn unique_names<T: Clone + std::fmt::Debug>(
+    value: impl Strategy<Value = T>,
+    size: std::ops::Range<usize>,
+) -> impl Strategy<Value = Vec<(String, T)>> {
+    prop::collection::btree_map(ident(), value, size)
+        .prop_map(|map| map.into_iter().collect::<Vec<_>>())
+        .prop_shuffle()
+}
+
+fn enums() -> impl Strategy<Value = Vec<AstEnum>> {
+    let variants = prop::collection::btree_set(ident(), 1..4)
+        .prop_map(|variants| variants.into_iter().collect::<Vec<_>>())
+        .prop_shuffle();
+    let repr = prop_oneof![Just("i64".to_string()), Just("text".to_string())];
+    unique_names((repr, variants), 0..3).prop_map(|enums| {
+        enums
+            .into_iter()
+            .filter(|(name, _)| {
+                !matches!(name.as_str(), "i64" | "text" | "bool" | "null")
+            })
+            .map(|(name, (repr, variants))| AstEnum {
+                name,
+                repr,
+                variants,
+                source: None,
+            })
+            .collect()
+    })
+}
+
+// Random choices for an expression over one column. Which of them are used
+// depends on the column's type.
+#[derive(Clone, Debug)]
+struct PredicateSeed {
+    shape: Index,
+    op: Index,
+    integer: i64,
+    variant: Index,
+}
+
+fn predicate_seed() -> impl Strategy<Value = PredicateSeed> {
+    (any::<Index>(), any::<Index>(), any::<i64>(), any::<Index>()).prop_map(
+        |(shape, op, integer, variant)| PredicateSeed {
+            shape,
+            op,
+            integer,
+            variant,
+        },
+    )
+}
+
+#[derive(Clone, Debug)]
+struct FieldSeed {
+    ty: Index,
+    key: bool,
+    auto_increment: bool,
+    default: Option<(i64, String)>,
+    computed: Option<Index>,
+    check: Option<(bool, PredicateSeed)>,
+}
+
+fn field_seed() -> impl Strategy<Value = FieldSeed> {
+    (
+        any::<Index>(),
+        any::<bool>(),
+        any::<bool>(),
+        prop::option::of((any::<i64>(), "[a-z ']{0,6}")),
+        prop::option::weighted(0.2, any::<Index>()),
+        prop::option::of((any::<bool>(), predicate_seed())),
+    )
+        .prop_map(|(ty, key, auto_increment, default, computed, check)| {
+            FieldSeed {
+                ty,
+                key,
+                auto_increment,
+                default,
+                computed,
+                check,
+            }
+        })
+}
+
+#[derive(Clone, Debug)]
+struct TableSeed {
+    fields: Vec<(String, FieldSeed)>,
+    checks: Vec<(Index, PredicateSeed)>,
+}
+
+fn table_seed() -> impl Strategy<Value = TableSeed> {
+    (
+        unique_names(field_seed(), 0..5),
+        prop::collection::vec((any::<Index>(), predicate_seed()), 0..2),
+    )
+        .prop_map(|(fields, checks)| TableSeed { fields, checks })
+}
+
+const COMPARISONS: [BinaryOp; 6] = [
+    BinaryOp::Eq,
+    BinaryOp::Ne,
+    BinaryOp::Lt,
+    BinaryOp::Le,
+    BinaryOp::Gt,
+    BinaryOp::Ge,
+];
+
+fn binary(op: BinaryOp, left: Expr, right: Expr) -> Expr {
+    Expr::Binary {
+        op,
+        left: Box::new(left),
+        right: Box::new(right),
+    }
+}
+
+// Builds a boolean expression over `field` that resolve accepts.
+fn predicate(
+    field: &AstField,
+    enums: &[AstEnum],
+    seed: &PredicateSeed,
+) -> Expr {
+    let column = Expr::Column(field.name.clone());
+    let op = *seed.op.get(&COMPARISONS);
+    let compared = match (field.ty.as_str(), find_enum(enums, &field.ty)) {
+        ("i64", _) => binary(op, column.clone(), Expr::Integer(seed.integer)),
+        (_, Some(enum_def)) => binary(
+            if seed.integer % 2 == 0 {
+                BinaryOp::Eq
+            } else {
+                BinaryOp::Ne
+            },
+            column.clone(),
+            Expr::Text(seed.variant.get(&enum_def.variants).clone()),
+        ),
+        _ => binary(
+            op,
+            Expr::Call {
+                function: "len".to_string(),
+                args: vec![column.clone()],
+            },
+            Expr::Integer(seed.integer),
+        ),
+    };
+    match seed.shape.index(4) {
+        0 => compared,
+        1 => Expr::Unary {
+            op: UnaryOp::Not,
+            operand: Box::new(compared),
+        },
+        2 => binary(
+            BinaryOp::Or,
+            Expr::IsNull {
+                operand: Box::new(column),
+                negated: false,
+            },
+            compared,
+        ),
+        _ => Expr::IsNull {
+            operand: Box::new(column),
+            negated: true,
+        },
+    }
+}
+
+fn find_enum<'a>(enums: &'a [AstEnum], name: &str) -> Option<&'a AstEnum> {
+    enums.iter().find(|enum_def| enum_def.name == name)
+}
+
+// Builds a computed expression of type `ty` from the plain columns declared
+// before it, or `None` if the type cannot be computed.
+fn computed(
+    ty: &str,
+    fields: &[AstField],
+    source: Index,
+    literal: i64,
+) -> Option<Expr> {
+    let sources = fields
+        .iter()
+        .filter(|field| field.ty == ty && field.computed.is_none())
+        .collect::<Vec<_>>();
+    let operand = if sources.is_empty() {
+        None
+    } else {
+        Some(Expr::Column(source.get(&sources).name.clone()))
+    };
+    match ty {
+        "i64" => Some(binary(
+            BinaryOp::Mul,
+            operand.unwrap_or(Expr::Integer(1)),
+            Expr::Integer(literal),
+        )),
+        "text" => Some(Expr::Call {
+            function: "upper".to_string(),
+            args: vec![binary(
+                BinaryOp::Concat,
+                operand.unwrap_or_else(|| Expr::Text(String::new())),
+                Expr::Text("x".to_string()),
+            )],
+        }),
+        _ => None,
+    }
+}
+
+fn build_table(name: String, seed: TableSeed, enums: &[AstEnum]) -> AstTable {
+    let mut types = vec!["i64".to_string(), "text".to_string()];
+    types.extend(enums.iter().map(|enum_def| enum_def.name.clone()));
+
+    let mut fields: Vec<AstField> = Vec::new();
+    let mut has_key = false;
+    let mut named_checks = 0;
+    for (field_name, field) in seed.fields {
+        let ty = field.ty.get(&types).clone();
+        let key = field.key && !has_key;
+        has_key |= key;
+        let auto_increment = key && field.auto_increment && ty == "i64";
+        let computed = match field.computed {
+            Some(source) if !key => computed(
+                &ty,
+                &fields,
+                source,
+                field.default.as_ref().map(|(value, _)| *value).unwrap_or(2),
+            ),
+            _ => None,
+        };
+        let default =
+            match (&field.default, auto_increment || computed.is_some()) {
+                (Some((value, text)), false) => {
+                    Some(match (ty.as_str(), find_enum(enums, &ty)) {
+                        ("i64", _) => Expr::Integer(*value),
+                        (_, Some(enum_def)) => Expr::Text(
+                            enum_def.variants[value.unsigned_abs() as usize
+                                % enum_def.variants.len()]
+                            .clone(),
+                        ),
+                        _ => Expr::Text(text.clone()),
+                    })
+                }
+                _ => None,
+            };
+        let mut ast_field = AstField {
+            name: field_name,
+            ty,
+            key,
+            auto_increment,
+            default,
+            computed,
+            checks: Vec::new(),
+        };
+        if let Some((named, check)) = &field.check {
+            let name = if *named {
+                named_checks += 1;
+                Some(format!("rule{}", named_checks))
+            } else {
+                None
+            };
+            ast_field.checks.push(AstCheck {
+                name,
+                expr: predicate(&ast_field, enums, check),
+            });
+        }
+        fields.push(ast_field);
+    }
+
+    let checks = if fields.is_empty() {
+        Vec::new()
+    } else {
+        seed.checks
+            .iter()
+            .map(|(field, check)| AstCheck {
+                name: None,
+                expr: predicate(field.get(&fields), enums, check),
+            })
+            .collect()
+    };
+
+    AstTable {
+        name,
+        fields,
+        uses: Vec::new(),
+        checks,
+        source: None,
+    }
+}
+
+fn references() -> impl Strategy<Value = (Index, Vec<Index>)> {
+    (any::<Index>(), prop::collection::vec(any::<Index>(), 0..4))
+}
+
+fn pick_columns<'a>(
+    table: &'a AstTable,
+    columns: &[Index],
+) -> Vec<&'a AstField> {
+    if table.fields.is_empty() {
+        return Vec::new();
+    }
+    columns
+        .iter()
+        .map(|index| index.get(&table.fields))
+        .collect()
+}
+
+fn schema() -> impl Strategy<Value = AstSchema> {
+    enums()
+        .prop_flat_map(|enums| {
+            (
+                Just(enums),
+                unique_names(table_seed(), 1..4),
+                unique_names(references(), 0..4),
+                unique_names(
+                    (references(), prop::option::of(predicate_seed())),
+                    0..4,
+                ),
+            )
+        })
+        .prop_map(|(enums, tables, procs, queries)| {
+            let tables = tables
+                .into_iter()
+                .map(|(name, seed)| build_table(name, seed, &enums))
+                .collect::<Vec<_>>();
+
+            // Auto-increment and computed columns cannot be proc params.
+            let procs = procs
+                .into_iter()
+                .map(|(name, (table, columns))| {
+                    let table = table.get(&tables);
+                    let mut params: Vec<AstParam> = Vec::new();
+                    for field in pick_columns(table, &columns) {
+                        if field.auto_increment || field.computed.is_some() {
+                            continue;
+                        }
+                        if params.iter().all(|param| param.name != field.name) {
+                            params.push(AstParam {
+                                name: field.name.clone(),
+                                ty: field.ty.clone(),
+                            });
+                        }
+                    }
+                    AstProc {
+                        name,
+                        table: table.name.clone(),
+                        params,
+                        source: None,
+                    }
+                })
+                .collect();
+
+            let queries = queries
+                .into_iter()
+                .map(|(name, ((table, columns), filter))| {
+                    let table = table.get(&tables);
+                    let projection = pick_columns(table, &columns);
+                    let filter = match (projection.first(), filter) {
+                        (Some(field), Some(seed)) => {
+                            Some(predicate(field, &enums, &seed))
+                        }
+                        _ => None,
+                    };
+                    AstQuery {
+                        name,
+                        table: table.name.clone(),
+                        projection: projection
+                            .into_iter()
+                            .map(|field| field.name.clone())
+                            .collect(),
+                        filter,
+                        source: None,
+                    }
+                })
+                .collect();
+
+            AstSchema {
+                includes: Vec::new(),
+                mixins: Vec::new(),
+                enums,
+                tables,
+                procs,
+                queries,
+                lint_allow: Vec::new(),
+            }
+        })
+}
+
+proptest! {
+    #[test]
+    fn schema_ir_round_trips_through_kdl(ast in schema()) {
+        let schema = passes::resolve::run(&ast).expect("resolve schema");
+        let printed = ir::schema::print_kdl(&schema);
+        let parsed = ir::schema::parse_kdl(&printed).unwrap_or_else(|err| {
+            panic!("failed to parse printed schema:\n{}\n{}", printed, err)
+        });
+        prop_assert_eq!(parsed, schema);
+    }
+
+    #[test]
+    fn sorted_schema_ir_reparses_with_same_names(ast in schema()) {
+        let schema = passes::resolve::run(&ast).expect("resolve schema");
+        let printed = ir::schema::print_kdl_with(&schema, PrintOrder::Sorted);
+        let parsed = ir::schema::parse_kdl(&printed).unwrap_or_else(|err| {
+            panic!("failed to parse printed schema:\n{}\n{}", printed, err)
+        });
+        prop_assert_eq!(
+            ir::schema::print_kdl_with(&parsed, PrintOrder::Sorted),
+            printed
+        );
+    }
+}
+
+#[test]
+fn negative_check_literals_round_trip() {
+    let ast = ir::ast::parse_kdl(
+        r#"
+table "people" {
+    field "age" type="i64" {
+        check {
+            ge {
+                column "age"
+                value -1
+            }
+        }
+    }
+    field "score" type="i64" {
+        check "score > -9223372036854775808"
+    }
+}
+"#,
+    )
+    .expect("parse schema");
+    let schema = passes::resolve::run(&ast).expect("resolve schema");
+    let printed = ir::schema::print_kdl(&schema);
+
+    assert_eq!(
+        ir::schema::parse_kdl(&printed).expect("parse printed schema"),
+        schema
+    );
+}
@@ -1,4 +1,5 @@
 use schemaforge::backend::sqlite::compile_plan_to_sql;
+use schemaforge::explain::render_plan_tree;
 use schemaforge::ir;
 use schemaforge::lower::lower_queries;
 use schemaforge::passes;
@@ -61,6 +62,22 @@ fn compiles_projection_plan_to_sqlite_sql() {
     );
 }
 
+#[test]
+fn renders_plan_tree_with_qualified_columns() {
+    let schema = load_resolved_schema();
+    let lowered = lower_queries(&schema).expect("lower query plans");
+
+    let query = lowered
+        .iter()
+        .find(|query| query.name == "list_names_and_ids")
+        .expect("query exists");
+
+    assert_eq!(
+        render_plan_tree(&query.plan, &schema),
+        "Project people.name, people.id\n  TableScan people\n"
+    );
+}
+
 fn load_resolved_schema() -> schemaforge::ir::schema::ResolvedSchema {
     let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
     let fixture = manifest_dir.join("tests/fixtures/spike/spike.in.kdl");
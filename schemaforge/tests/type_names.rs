@@ -0,0 +1,87 @@
+use schemaforge::build::{render_module, render_module_with};
+use schemaforge::ir::ast::parse_kdl;
+use schemaforge::passes;
+
+fn render(input: &str) -> Result<String, String> {
+    let ast = parse_kdl(input).expect("parse schema");
+    let schema = passes::run_frontend(&ast).expect("resolve schema");
+    render_module(&schema).map_err(|err| err.to_string())
+}
+
+fn with_enum(name: &str) -> String {
+    format!(
+        r#"
+enum "{}" {{
+    variant "a"
+}}
+
+table "people" {{
+    field "name" type="text"
+}}
+
+proc "add_person" table="people" {{
+    param "name" type="text"
+}}
+"#,
+        name
+    )
+}
+
+#[test]
+fn rejects_enums_named_like_generated_types() {
+    for (name, type_name) in
+        [("db", "Db"), ("async_db", "AsyncDb"), ("error", "Error")]
+    {
+        assert_eq!(
+            render(&with_enum(name)),
+            Err(format!(
+                "pass error: enum '{}' conflicts with the generated {} type",
+                name, type_name
+            ))
+        );
+    }
+}
+
+#[test]
+fn rejects_enums_named_like_proc_rows() {
+    assert_eq!(
+        render(&with_enum("add_person_row")),
+        Err(
+            "pass error: enum 'add_person_row' conflicts with the row type \
+             'AddPersonRow' of proc 'add_person'"
+                .to_string()
+        )
+    );
+}
+
+#[test]
+fn rejects_enums_mapping_to_the_same_type() {
+    let input = r#"
+enum "ticket_status" {
+    variant "open"
+}
+
+enum "ticket-status" {
+    variant "open"
+}
+"#;
+
+    assert_eq!(
+        render(input),
+        Err(
+            "pass error: enums 'ticket_status' and 'ticket-status' both map \
+             to Rust type 'TicketStatus'"
+                .to_string()
+        )
+    );
+}
+
+#[test]
+fn accepts_other_enum_names() {
+    assert!(render(&with_enum("dbs")).is_ok());
+
+    let ast = parse_kdl(&with_enum("job")).expect("parse schema");
+    let schema = passes::run_frontend(&ast).expect("resolve schema");
+    let module = render_module_with(&schema, true).expect("render module");
+    assert!(module.contains("pub enum Job {"));
+}